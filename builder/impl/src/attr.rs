@@ -0,0 +1,132 @@
+//! Parsing of the `#[builder(...)]` attributes.
+//!
+//! Each option is parsed on its own: an error in one option is recorded and
+//! parsing goes on with the next one, so that all the mistakes in the
+//! attributes of the input are reported by a single compile.
+
+use syn::meta::ParseNestedMeta;
+use syn::parse::Parse;
+
+/// Errors collected while parsing, combined with [`syn::Error::combine`].
+#[derive(Default)]
+pub(crate) struct Errors(Option<syn::Error>);
+
+impl Errors {
+    pub(crate) fn push(&mut self, err: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    /// Fails with all the collected errors, if there are any.
+    pub(crate) fn finish(self) -> syn::Result<()> {
+        match self.0 {
+            Some(errors) => Err(errors),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Calls `parse` for each option in the `#[builder(...)]` attributes among
+/// `attrs`.
+pub(crate) fn parse_builder_attrs(
+    attrs: &[syn::Attribute],
+    errors: &mut Errors,
+    mut parse: impl FnMut(&ParseNestedMeta, &mut Errors) -> syn::Result<()>,
+) {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+        let result = attr.parse_nested_meta(|meta| recover(&meta, errors, &mut parse));
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+}
+
+/// Calls `parse` for each option nested in a list option such as
+/// `setter(...)`.
+pub(crate) fn parse_nested(
+    meta: &ParseNestedMeta,
+    errors: &mut Errors,
+    mut parse: impl FnMut(&ParseNestedMeta, &mut Errors) -> syn::Result<()>,
+) -> syn::Result<()> {
+    meta.parse_nested_meta(|meta| recover(&meta, errors, &mut parse))
+}
+
+/// Parses one option with `parse`. If that fails, the error is recorded and
+/// whatever is left of the option is skipped, up to the comma before the
+/// next one.
+fn recover(
+    meta: &ParseNestedMeta,
+    errors: &mut Errors,
+    parse: &mut impl FnMut(&ParseNestedMeta, &mut Errors) -> syn::Result<()>,
+) -> syn::Result<()> {
+    if let Err(err) = parse(meta, errors) {
+        errors.push(err);
+        while !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+            meta.input.parse::<proc_macro2::TokenTree>()?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses the value of an option written `key = "..."` as a `T`. Errors in
+/// the string point at the string.
+pub(crate) fn parse_str<T: Parse>(meta: &ParseNestedMeta) -> syn::Result<T> {
+    let s: syn::LitStr = meta.value()?.parse()?;
+    s.parse()
+}
+
+/// Parses the value of an option written `vis = "..."`, where an empty
+/// string stands for private.
+pub(crate) fn parse_vis(meta: &ParseNestedMeta) -> syn::Result<syn::Visibility> {
+    let s: syn::LitStr = meta.value()?.parse()?;
+    s.parse().map_err(|_| {
+        syn::Error::new(
+            s.span(),
+            "expected a visibility such as `\"pub\"` or `\"pub(crate)\"`, or `\"\"` for private",
+        )
+    })
+}
+
+/// The error for an option that is none of the `expected` ones. When the
+/// option looks like a misspelling of one of them, that one is suggested.
+pub(crate) fn unknown_option(meta: &ParseNestedMeta, expected: &[&str]) -> syn::Error {
+    let path = &meta.path;
+    let name = quote::quote!(#path).to_string().replace(' ', "");
+    let suggestion = expected
+        .iter()
+        .map(|option| (edit_distance(&name, option), option))
+        .filter(|(distance, option)| *distance <= (option.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance);
+    match suggestion {
+        Some((_, option)) => meta.error(format!(
+            "unknown `builder` option `{}`, did you mean `{}`?",
+            name, option
+        )),
+        None => {
+            let expected: Vec<String> = expected.iter().map(|e| format!("`{}`", e)).collect();
+            meta.error(format!(
+                "unknown `builder` option `{}`, expected one of {}",
+                name,
+                expected.join(", ")
+            ))
+        }
+    }
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
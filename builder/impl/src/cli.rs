@@ -0,0 +1,212 @@
+//! Filling a builder from command-line arguments, for `#[builder(cli)]`.
+//!
+//! Every field is set by a `--flag` named after it in kebab case, followed
+//! by its value either as the next argument or after `=`. Values are parsed
+//! with `FromStr`. `bool` fields are switches that need no value, and fields
+//! with `each = "..."` have a flag named after the one-at-a-time setter,
+//! which is given once per element, `KEY=VALUE` for maps. `sub_builder`
+//! fields have no flag. `--help` or `-h` fails with the help text, which
+//! lists the flags.
+//!
+//! Every argument is taken for a flag or its value, so the program name that
+//! `std::env::args()` starts with has to be skipped.
+
+use crate::{BuilderField, BuilderTarget};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+
+/// The name of the setter that the flag of `field` stands for.
+fn setter_name(field: &BuilderField) -> String {
+    let name = match &field.each {
+        Some(each) => &each.name,
+        None => &field.ident,
+    };
+    name.unraw().to_string()
+}
+
+/// The `--flag` that sets `field`.
+fn flag(field: &BuilderField) -> String {
+    format!("--{}", setter_name(field).replace('_', "-"))
+}
+
+/// Whether the type is `bool`, whose fields are switches.
+pub(crate) fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(p) if p.qself.is_none() && p.path.is_ident("bool"))
+}
+
+/// The help text printed for `--help`.
+fn help(fields: &[BuilderField], target: &BuilderTarget) -> String {
+    let rows: Vec<(String, String)> = fields
+        .iter()
+        .filter(|field| field.is_stored() && field.sub_builder.is_none())
+        .map(|field| {
+            let ty = field.option_inner().unwrap_or(&field.ty);
+            let value = setter_name(field).to_uppercase();
+            let usage = match &field.each {
+                Some(each) if each.item.key.is_some() => {
+                    format!("{} <KEY=VALUE>...", flag(field))
+                }
+                Some(_) => format!("{} <{}>...", flag(field), value),
+                None if is_bool(ty) => flag(field),
+                None => format!("{} <{}>", flag(field), value),
+            };
+            let mut description = field.doc.clone().unwrap_or_default();
+            if field.is_required() {
+                if !description.is_empty() {
+                    description.push(' ');
+                }
+                description.push_str("(required)");
+            }
+            (usage, description)
+        })
+        .chain([("-h, --help".to_owned(), "Print this help".to_owned())])
+        .collect();
+
+    let width = rows.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+    let mut help = format!("Usage: {} [OPTIONS]\n\nOptions:\n", target.output);
+    for (usage, description) in rows {
+        let line = format!("  {:width$}  {}", usage, description, width = width);
+        help.push_str(line.trim_end());
+        help.push('\n');
+    }
+    help
+}
+
+/// `parse_args`, which fills a new builder from command-line arguments, if
+/// the builder is `#[builder(cli)]`.
+pub(crate) fn expand_cli(fields: &[BuilderField], target: &BuilderTarget) -> Option<TokenStream2> {
+    target.options.cli.as_ref()?;
+    let BuilderTarget {
+        output,
+        generics,
+        builder_fn,
+        ..
+    } = target;
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let error_name = target.error_name();
+    let build_error = target.build_error();
+    let vis = target.options.method_vis();
+    let help = help(fields, target);
+
+    let invalid = |message: TokenStream2| {
+        quote! {
+            ::core::convert::From::from(#error_name::InvalidArgs(#message))
+        }
+    };
+    let parse = |ty: &syn::Type, value: TokenStream2, flag: &str| {
+        let invalid = invalid(quote! {
+            ::std::format!("invalid value for `{}`: {}", #flag, err)
+        });
+        quote! {
+            <#ty as ::core::str::FromStr>::from_str(&#value).map_err(|err| -> #build_error { #invalid })?
+        }
+    };
+
+    let arms = fields.iter().filter(|field| field.is_stored() && field.sub_builder.is_none()).map(|field| {
+        let field_id = &field.ident;
+        let flag = flag(field);
+        let ty = field.option_inner().unwrap_or(&field.ty);
+
+        if field.each.is_none() && is_bool(ty) {
+            let value = parse(ty, quote! { value }, &flag);
+            return quote! {
+                #flag => {
+                    let value = match inline {
+                        ::core::option::Option::Some(value) => #value,
+                        ::core::option::Option::None => true,
+                    };
+                    builder.#field_id = Some(value);
+                }
+            };
+        }
+
+        let missing = invalid(quote! {
+            ::std::format!("missing value for `{}`", #flag)
+        });
+        let take_value = quote! {
+            let value = match inline {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => match args.next() {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => return ::core::result::Result::Err(#missing),
+                },
+            };
+        };
+        let assign = match &field.each {
+            None => {
+                let value = parse(ty, quote! { value }, &flag);
+                quote! {
+                    builder.#field_id = Some(#value);
+                }
+            }
+            Some(each) => {
+                let item = match &each.item.key {
+                    None => parse(&each.item.value, quote! { value }, &flag),
+                    Some(key_ty) => {
+                        let not_pair = invalid(quote! {
+                            ::std::format!("invalid value for `{}`: expected KEY=VALUE", #flag)
+                        });
+                        let key = parse(key_ty, quote! { key }, &flag);
+                        let value = parse(&each.item.value, quote! { value }, &flag);
+                        quote! {{
+                            let (key, value) = match value.split_once('=') {
+                                ::core::option::Option::Some(pair) => pair,
+                                ::core::option::Option::None => {
+                                    return ::core::result::Result::Err(#not_pair)
+                                }
+                            };
+                            (#key, #value)
+                        }}
+                    }
+                };
+                quote! {
+                    let item = #item;
+                    ::core::iter::Extend::extend(
+                        builder.#field_id.get_or_insert_with(::core::default::Default::default),
+                        ::core::iter::once(item),
+                    );
+                }
+            }
+        };
+        quote! {
+            #flag => {
+                #take_value
+                #assign
+            }
+        }
+    });
+    let help_requested = quote! {
+        ::core::convert::From::from(#error_name::HelpRequested(::std::string::ToString::to_string(#help)))
+    };
+    let unknown = invalid(quote! {
+        ::std::format!("unknown argument `{}`", flag)
+    });
+
+    Some(quote! {
+        /// Fills a new builder from command-line arguments, without the
+        /// program name: pass `std::env::args().skip(1)`. Fails with
+        /// `HelpRequested` and the help text for `--help` or `-h`.
+        #vis fn parse_args(
+            args: impl ::core::iter::IntoIterator<Item = ::std::string::String>,
+        ) -> ::core::result::Result<Self, #build_error> {
+            let mut builder = <#output #ty_generics>::#builder_fn();
+            let mut args = ::core::iter::IntoIterator::into_iter(args);
+            while let ::core::option::Option::Some(arg) = args.next() {
+                let (flag, inline) = match arg.split_once('=') {
+                    ::core::option::Option::Some((flag, value)) if flag.starts_with("--") => (
+                        ::std::string::ToString::to_string(flag),
+                        ::core::option::Option::Some(::std::string::ToString::to_string(value)),
+                    ),
+                    _ => (arg, ::core::option::Option::None),
+                };
+                match flag.as_str() {
+                    "--help" | "-h" => return ::core::result::Result::Err(#help_requested),
+                    #(#arms)*
+                    _ => return ::core::result::Result::Err(#unknown),
+                }
+            }
+            ::core::result::Result::Ok(builder)
+        }
+    })
+}
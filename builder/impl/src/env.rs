@@ -0,0 +1,82 @@
+//! Filling a builder from environment variables.
+//!
+//! Fields with `#[builder(env = "...")]`, or every field under
+//! `#[builder(env_prefix = "...")]`, are read from their variable by
+//! `fill_from_env` and `from_env`. The value is parsed with `FromStr` into
+//! the field type, or into `T` for an `Option<T>` field. Variables that are
+//! not set leave the field as it was. `from_env` is left out for builders
+//! with `start` fields, which cannot be created without their values.
+
+use crate::{BuilderField, BuilderTarget};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+/// The methods that fill the builder from the environment, if any field is
+/// read from a variable.
+pub(crate) fn expand_env(fields: &[BuilderField], target: &BuilderTarget) -> Option<TokenStream2> {
+    if !uses_env(fields) {
+        return None;
+    }
+    let BuilderTarget {
+        output,
+        generics,
+        builder_fn,
+        ..
+    } = target;
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let error_name = target.error_name();
+    let build_error = target.build_error();
+    let vis = target.options.method_vis();
+
+    let reads = fields.iter().filter_map(|field| {
+        let var = field.env.as_ref()?;
+        let field_id = &field.ident;
+        let ty = field.option_inner().unwrap_or(&field.ty);
+        Some(quote! {
+            match ::std::env::var(#var) {
+                ::core::result::Result::Ok(value) => {
+                    let value = <#ty as ::core::str::FromStr>::from_str(&value).map_err(|err| {
+                        #error_name::InvalidEnvVar {
+                            name: #var,
+                            message: ::std::string::ToString::to_string(&err),
+                        }
+                    })?;
+                    self.#field_id = Some(value);
+                }
+                ::core::result::Result::Err(::std::env::VarError::NotPresent) => {}
+                ::core::result::Result::Err(::std::env::VarError::NotUnicode(_)) => {
+                    return ::core::result::Result::Err(::core::convert::From::from(
+                        #error_name::InvalidEnvVar {
+                            name: #var,
+                            message: ::std::string::ToString::to_string("not valid unicode"),
+                        },
+                    ));
+                }
+            }
+        })
+    });
+
+    let from_env = (!fields.iter().any(|field| field.start)).then(|| {
+        quote! {
+            #vis fn from_env() -> ::core::result::Result<Self, #build_error> {
+                let mut builder = <#output #ty_generics>::#builder_fn();
+                builder.fill_from_env()?;
+                ::core::result::Result::Ok(builder)
+            }
+        }
+    });
+
+    Some(quote! {
+        #vis fn fill_from_env(&mut self) -> ::core::result::Result<&mut Self, #build_error> {
+            #(#reads)*
+            ::core::result::Result::Ok(self)
+        }
+
+        #from_env
+    })
+}
+
+/// Whether any of the fields is read from an environment variable.
+pub(crate) fn uses_env(fields: &[BuilderField]) -> bool {
+    fields.iter().any(|field| field.env.is_some())
+}
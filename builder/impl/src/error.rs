@@ -0,0 +1,231 @@
+//! The error type returned by `build`.
+//!
+//! Every builder gets its own error enum, named after the builder with an
+//! `Error` suffix, so that callers can match on what went wrong. With
+//! `#[builder(error = "...")]` `build` returns the given type instead, which
+//! must implement `From` for the generated enum.
+//!
+//! The enums implement `derive_builder::BuildError`, which `Display` goes
+//! through: the errors of `sub_builder` fields are kept as they are, and name
+//! the fields they are about by their path from the outermost builder.
+
+use crate::{camel_case, env, group, validate, BuilderField, BuilderTarget};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+/// The variant of the field's error in the enum of the errors of
+/// `sub_builder` fields.
+pub(crate) fn field_variant(field: &BuilderField) -> syn::Ident {
+    let name = camel_case(&field.ident);
+    // Tuple fields have positional names such as `_0`.
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format_ident!("_{}", name)
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+pub(crate) fn expand_error(fields: &[BuilderField], target: &BuilderTarget) -> TokenStream2 {
+    let error_name = target.error_name();
+    let vis = target.vis;
+    let alloc = target.options.alloc_crate();
+
+    // Variants for the features in use only.
+    let mut variants = Vec::new();
+    let mut display_arms = Vec::new();
+    if env::uses_env(fields) {
+        variants.push(quote! {
+            /// An environment variable could not be parsed into its field.
+            InvalidEnvVar {
+                name: &'static str,
+                message: #alloc::string::String,
+            },
+        });
+        display_arms.push(quote! {
+            #error_name::InvalidEnvVar { name, message } => {
+                ::core::write!(f, "invalid value for {}: {}", name, message)
+            }
+        });
+    }
+    let field_error = expand_field_error(fields, target);
+    if field_error.is_some() {
+        let field_error_name = target.field_error_name();
+        variants.push(quote! {
+            /// The builder of a `sub_builder` field failed.
+            SubBuilder(#field_error_name),
+        });
+        display_arms.push(quote! {
+            #error_name::SubBuilder(err) => ::derive_builder::BuildError::fmt_at(err, path, f),
+        });
+    }
+    if validate::uses_validators(fields) {
+        variants.push(quote! {
+            /// Values were rejected by the fields' validators, with a
+            /// message for each violation that starts with the field's name.
+            InvalidFields(#alloc::vec::Vec<#alloc::string::String>),
+        });
+        display_arms.push(quote! {
+            #error_name::InvalidFields(violations) => {
+                f.write_str("invalid fields: ")?;
+                for (i, violation) in violations.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    ::core::write!(f, "{}", ::derive_builder::FieldPath::new(path, violation))?;
+                }
+                ::core::result::Result::Ok(())
+            }
+        });
+    }
+    for (variant, display_arm) in group::error_variants(fields, target) {
+        variants.push(variant);
+        display_arms.push(display_arm);
+    }
+    if target.options.cli.is_some() {
+        variants.push(quote! {
+            /// The command-line arguments could not be parsed.
+            InvalidArgs(#alloc::string::String),
+            /// `--help` was given, the help text is the variant's value.
+            HelpRequested(#alloc::string::String),
+        });
+        display_arms.push(quote! {
+            #error_name::InvalidArgs(message) | #error_name::HelpRequested(message) => {
+                f.write_str(message)
+            }
+        });
+    }
+
+    quote! {
+        #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+        #vis enum #error_name {
+            /// A required field was never set. `build` reports the fields it
+            /// finds missing with `MissingFields`, this is for code that
+            /// checks a single field, such as a custom `build`.
+            MissingField(&'static str),
+            /// Required fields were never set, named by their path if they
+            /// are fields of `sub_builder` fields. `build` reports every
+            /// missing field with this variant, even if only one is missing.
+            MissingFields(#alloc::vec::Vec<#alloc::string::String>),
+            /// Several of the checks that `build` makes failed, with the
+            /// error of each in the order they are made: missing fields,
+            /// groups, `required_if` and validators.
+            Multiple(#alloc::vec::Vec<#error_name>),
+            /// The builder's values were rejected by a validation.
+            ValidationError(#alloc::string::String),
+            #(#variants)*
+        }
+
+        impl ::derive_builder::BuildError for #error_name {
+            fn fmt_at(
+                &self,
+                path: ::core::option::Option<&::derive_builder::FieldPath<'_>>,
+                f: &mut ::core::fmt::Formatter<'_>,
+            ) -> ::core::fmt::Result {
+                match self {
+                    #error_name::MissingField(field) => {
+                        ::core::write!(f, "{} is not set", ::derive_builder::FieldPath::new(path, field))
+                    }
+                    #error_name::MissingFields(fields) if fields.len() == 1 => {
+                        ::core::write!(f, "{} is not set", ::derive_builder::FieldPath::new(path, &fields[0]))
+                    }
+                    #error_name::MissingFields(fields) => {
+                        f.write_str("missing fields: ")?;
+                        for (i, field) in fields.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(", ")?;
+                            }
+                            ::core::write!(f, "{}", ::derive_builder::FieldPath::new(path, field))?;
+                        }
+                        ::core::result::Result::Ok(())
+                    }
+                    #error_name::Multiple(errors) => {
+                        for (i, err) in errors.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str("; ")?;
+                            }
+                            ::derive_builder::BuildError::fmt_at(err, path, f)?;
+                        }
+                        ::core::result::Result::Ok(())
+                    }
+                    #error_name::ValidationError(message) => match path {
+                        ::core::option::Option::Some(path) => ::core::write!(f, "{}: {}", path, message),
+                        ::core::option::Option::None => f.write_str(message),
+                    },
+                    #(#display_arms)*
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::derive_builder::BuildError::fmt_at(self, ::core::option::Option::None, f)
+            }
+        }
+
+        impl ::core::error::Error for #error_name {}
+
+        #field_error
+    }
+}
+
+/// The enum of the errors of the builders of `sub_builder` fields, with a
+/// variant for each field that holds the error of its builder.
+fn expand_field_error(fields: &[BuilderField], target: &BuilderTarget) -> Option<TokenStream2> {
+    let fields: Vec<_> = fields
+        .iter()
+        .filter_map(|field| Some((field, field.sub_builder.as_ref()?)))
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+    let field_error_name = target.field_error_name();
+    let vis = target.vis;
+    let alloc = target.options.alloc_crate();
+
+    let variants = fields.iter().map(|(field, sub_builder)| {
+        let variant = field_variant(field);
+        // The field's builder is only known by its type, which names the
+        // error of its `build` through a hidden trait.
+        quote! {
+            #variant(#alloc::boxed::Box<<#sub_builder as ::derive_builder::__private::BuildFnError>::Error>),
+        }
+    });
+    let arms = fields.iter().map(|(field, _)| {
+        let variant = field_variant(field);
+        let name = field.ident.to_string();
+        quote! {
+            #field_error_name::#variant(err) => ::derive_builder::BuildError::fmt_at(
+                &**err,
+                ::core::option::Option::Some(&::derive_builder::FieldPath::new(path, #name)),
+                f,
+            ),
+        }
+    });
+
+    Some(quote! {
+        #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+        #vis enum #field_error_name {
+            #(#variants)*
+        }
+
+        impl ::derive_builder::BuildError for #field_error_name {
+            fn fmt_at(
+                &self,
+                path: ::core::option::Option<&::derive_builder::FieldPath<'_>>,
+                f: &mut ::core::fmt::Formatter<'_>,
+            ) -> ::core::fmt::Result {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #field_error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::derive_builder::BuildError::fmt_at(self, ::core::option::Option::None, f)
+            }
+        }
+
+        impl ::core::error::Error for #field_error_name {}
+    })
+}
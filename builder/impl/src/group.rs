@@ -0,0 +1,302 @@
+//! Rules that involve several fields.
+//!
+//! Fields with `#[builder(group = "...")]` form a group, of which either
+//! `exactly_one` or `at_most_one` field may be set, and a field with
+//! `#[builder(required_if = "...")]` has to be set whenever the field it
+//! names is. `build` checks both at run time, except that `typestate`
+//! builders track the fields of a group in a type parameter instead, so that
+//! setting a second one or building without one is a type error.
+
+use crate::attr::Errors;
+use crate::{BuilderField, BuilderTarget};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+
+/// How many fields of a group may be set.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Rule {
+    ExactlyOne,
+    AtMostOne,
+}
+
+impl Rule {
+    fn name(self) -> &'static str {
+        match self {
+            Rule::ExactlyOne => "exactly_one",
+            Rule::AtMostOne => "at_most_one",
+        }
+    }
+}
+
+/// The group of a field as given in its attributes. The rule of the group
+/// has to be given on at least one of its fields.
+pub(crate) struct FieldGroup {
+    pub(crate) name: syn::Ident,
+    pub(crate) rule: Option<(Rule, Span)>,
+}
+
+/// A group of fields with its rule.
+pub(crate) struct Group {
+    pub(crate) name: syn::Ident,
+    pub(crate) rule: Rule,
+    /// Indices of the group's fields.
+    pub(crate) members: Vec<usize>,
+}
+
+/// Collects the groups of `fields`, and checks that every group has one rule
+/// and that every `required_if` names a field that can be set.
+pub(crate) fn resolve(fields: &[BuilderField], errors: &mut Errors) -> Vec<Group> {
+    let mut groups: Vec<(Group, Option<Span>)> = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let Some(field_group) = &field.group else {
+            continue;
+        };
+        let position = groups
+            .iter()
+            .position(|(group, _)| group.name == field_group.name);
+        let (group, rule_span) = match position {
+            Some(position) => &mut groups[position],
+            None => {
+                groups.push((
+                    Group {
+                        name: field_group.name.clone(),
+                        rule: Rule::AtMostOne,
+                        members: Vec::new(),
+                    },
+                    None,
+                ));
+                groups.last_mut().unwrap()
+            }
+        };
+        group.members.push(index);
+        if let Some((rule, span)) = field_group.rule {
+            match rule_span {
+                Some(_) if group.rule != rule => errors.push(syn::Error::new(
+                    span,
+                    format!("group `{}` is already `{}`", group.name, group.rule.name()),
+                )),
+                Some(_) => {}
+                None => {
+                    group.rule = rule;
+                    *rule_span = Some(span);
+                }
+            }
+        }
+    }
+
+    for field in fields {
+        let Some(other) = &field.required_if else {
+            continue;
+        };
+        let found = fields.iter().find(|f| f.ident == *other);
+        let message = match found {
+            None => Some(format!("there is no field `{}`", other)),
+            Some(found) if found.ident == field.ident => {
+                Some("a field cannot be required by itself".to_owned())
+            }
+            Some(found) if !found.is_stored() || found.sub_builder.is_some() => Some(format!(
+                "`{}` has no setter that could leave it unset",
+                other
+            )),
+            Some(_) => None,
+        };
+        if let Some(message) = message {
+            errors.push(syn::Error::new(other.span(), message));
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(group, rule_span)| {
+            if rule_span.is_none() {
+                errors.push(syn::Error::new(
+                    group.name.span(),
+                    format!(
+                        "group `{}` needs `exactly_one` or `at_most_one` on one of its fields",
+                        group.name
+                    ),
+                ));
+                return None;
+            }
+            Some(group)
+        })
+        .collect()
+}
+
+/// The name of a field in errors.
+fn field_name(field: &BuilderField) -> String {
+    field.ident.unraw().to_string()
+}
+
+/// Whether the field is set, in `build`.
+fn is_set(field: &BuilderField, target: &BuilderTarget) -> TokenStream2 {
+    let value = field.set_value(target);
+    quote! { #value.is_some() }
+}
+
+/// The checks that `build` makes before building the value, which add an
+/// error to `failures` for each rule that is broken.
+pub(crate) fn expand_checks(fields: &[BuilderField], target: &BuilderTarget) -> TokenStream2 {
+    let error_name = target.error_name();
+    let alloc = target.options.alloc_crate();
+    let fail = |error: TokenStream2| {
+        quote! {
+            failures.push(#error_name::#error);
+        }
+    };
+
+    let groups = target
+        .groups
+        .iter()
+        .filter(|_| !target.options.typestate)
+        .map(|group| {
+            let group_name = group.name.unraw().to_string();
+            let members: Vec<&BuilderField> =
+                group.members.iter().map(|&index| &fields[index]).collect();
+            let names = members.iter().map(|field| field_name(field));
+            let set = members.iter().map(|field| is_set(field, target));
+            let conflict = fail(quote! {
+                ConflictingFields { group: #group_name, fields: set }
+            });
+            let missing = (group.rule == Rule::ExactlyOne).then(|| {
+                let names = members.iter().map(|field| field_name(field));
+                let missing = fail(quote! {
+                    MissingGroup { group: #group_name, fields: &[#(#names),*] }
+                });
+                quote! {
+                    else if set.is_empty() {
+                        #missing
+                    }
+                }
+            });
+            quote! {
+                {
+                    let set: #alloc::vec::Vec<&'static str> = [#((#names, #set)),*]
+                        .into_iter()
+                        .filter_map(|(field, set)| set.then_some(field))
+                        .collect();
+                    if set.len() > 1 {
+                        #conflict
+                    }
+                    #missing
+                }
+            }
+        });
+
+    let required_if = fields.iter().filter_map(|field| {
+        let other = fields
+            .iter()
+            .find(|f| Some(&f.ident) == field.required_if.as_ref())?;
+        let name = field_name(field);
+        let other_name = field_name(other);
+        let field_set = is_set(field, target);
+        let other_set = is_set(other, target);
+        let missing = fail(quote! {
+            RequiredIf { field: #name, other: #other_name }
+        });
+        Some(quote! {
+            if #other_set && !#field_set {
+                #missing
+            }
+        })
+    });
+
+    quote! {
+        #(#groups)*
+        #(#required_if)*
+    }
+}
+
+/// The `(variant, Display arm)` pairs of the error enum for the rules that
+/// `build` checks at run time.
+pub(crate) fn error_variants(
+    fields: &[BuilderField],
+    target: &BuilderTarget,
+) -> Vec<(TokenStream2, TokenStream2)> {
+    let error_name = target.error_name();
+    let alloc = target.options.alloc_crate();
+    let list = quote! {
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            ::core::write!(f, "{}", ::derive_builder::FieldPath::new(path, field))?;
+        }
+    };
+
+    let mut variants = Vec::new();
+    if !target.options.typestate && !target.groups.is_empty() {
+        variants.push((
+            quote! {
+                /// More than one field of an `exactly_one` or `at_most_one`
+                /// group was set.
+                ConflictingFields {
+                    group: &'static str,
+                    fields: #alloc::vec::Vec<&'static str>,
+                },
+            },
+            quote! {
+                #error_name::ConflictingFields { group, fields } => {
+                    ::core::write!(
+                        f,
+                        "only one field of group {} can be set, got ",
+                        ::derive_builder::FieldPath::new(path, group),
+                    )?;
+                    #list
+                    ::core::result::Result::Ok(())
+                }
+            },
+        ));
+    }
+    if !target.options.typestate
+        && target
+            .groups
+            .iter()
+            .any(|group| group.rule == Rule::ExactlyOne)
+    {
+        variants.push((
+            quote! {
+                /// No field of an `exactly_one` group was set.
+                MissingGroup {
+                    group: &'static str,
+                    fields: &'static [&'static str],
+                },
+            },
+            quote! {
+                #error_name::MissingGroup { group, fields } => {
+                    ::core::write!(
+                        f,
+                        "one field of group {} must be set: ",
+                        ::derive_builder::FieldPath::new(path, group),
+                    )?;
+                    #list
+                    ::core::result::Result::Ok(())
+                }
+            },
+        ));
+    }
+    if fields.iter().any(|field| field.required_if.is_some()) {
+        variants.push((
+            quote! {
+                /// A `required_if` field was unset while the field it depends
+                /// on was set.
+                RequiredIf {
+                    field: &'static str,
+                    other: &'static str,
+                },
+            },
+            quote! {
+                #error_name::RequiredIf { field, other } => {
+                    ::core::write!(
+                        f,
+                        "{} is required when {} is set",
+                        ::derive_builder::FieldPath::new(path, field),
+                        ::derive_builder::FieldPath::new(path, other),
+                    )
+                }
+            },
+        ));
+    }
+    variants
+}
@@ -0,0 +1,1645 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+
+mod attr;
+mod cli;
+mod env;
+mod error;
+mod group;
+mod setter;
+mod traits;
+mod typestate;
+mod validate;
+
+use attr::Errors;
+use setter::{Each, EachItem, SetterArgs, SetterOptions};
+
+fn is_option(ty: &syn::Type) -> bool {
+    let syn::Type::Path(tp) = ty else {
+        return false;
+    };
+
+    tp.path
+        .segments
+        .first()
+        .is_some_and(|s| s.ident == "Option")
+}
+
+/// The builder that the derive names after `ty`, e.g. `path::TlsBuilder<T>`
+/// for `path::Tls<T>`.
+fn builder_of(ty: &syn::Type) -> Option<syn::Type> {
+    let syn::Type::Path(tp) = ty else {
+        return None;
+    };
+    if tp.qself.is_some() || is_option(ty) {
+        return None;
+    }
+    let mut tp = tp.clone();
+    let segment = tp.path.segments.last_mut()?;
+    segment.ident = format_ident!("{}Builder", segment.ident);
+    Some(syn::Type::Path(tp))
+}
+
+/// The first line of the doc comment among `attrs`, if there is one.
+fn doc_summary(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(doc),
+                    ..
+                }),
+            ..
+        }) if path.is_ident("doc") => Some(doc.value().trim().to_owned()),
+        _ => None,
+    })
+}
+
+/// Converts a `CamelCase` identifier such as an enum variant name into
+/// `snake_case`. A run of capitals is an acronym and stays one word, so
+/// `HTTPServer` becomes `http_server`.
+fn snake_case(ident: &syn::Ident) -> String {
+    let chars: Vec<char> = ident.to_string().chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            let boundary = match prev {
+                Some(prev) if prev.is_uppercase() => next.is_some_and(|c| c.is_lowercase()),
+                Some(prev) => prev != '_',
+                None => false,
+            };
+            if boundary {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Converts a `snake_case` field name into `CamelCase`, for naming types
+/// and variants after it.
+fn camel_case(ident: &syn::Ident) -> String {
+    let ident = ident.unraw().to_string();
+    let mut out = String::new();
+    for word in ident.split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.extend(chars);
+        }
+    }
+    out
+}
+
+/// Options given to `#[builder(...)]` on the input type itself.
+#[derive(Default)]
+struct BuilderOptions {
+    /// Track required fields in the builder's type, see [`typestate`].
+    typestate: bool,
+    /// `#[builder(default)]`: fields that are never set take their value from
+    /// the input type's `Default` impl. Holds the attribute's path for error
+    /// reporting.
+    default: Option<syn::Path>,
+    /// `#[builder(no_from)]`: newtypes do not implement `From` for their
+    /// field, typically because they implement it themselves.
+    no_from: bool,
+    /// `#[builder(no_std)]`: the generated code only uses `core` and
+    /// `alloc`, which the crate has to declare with `extern crate alloc`.
+    no_std: bool,
+    /// `#[builder(error = "...")]`: the error type returned by `build`.
+    error: Option<syn::Path>,
+    /// `#[builder(pattern = "...")]`, holding the attribute's value for
+    /// error reporting if it was given.
+    pattern: Pattern,
+    pattern_span: Option<proc_macro2::Span>,
+    /// `#[builder(setter(...))]`: defaults for the setters of all fields.
+    setter: SetterArgs,
+    /// `#[builder(build_fn(...))]`, see [`BuildFnOptions`].
+    build_fn: BuildFnOptions,
+    /// `#[builder(merge_fn)]`: the builder has a `merge` method that takes
+    /// over the fields set in another builder. The builders of `sub_builder`
+    /// fields are merged with their own `merge`, so they need it too.
+    merge_fn: Option<syn::Path>,
+    /// `#[builder(vis = "...")]`: visibility of the setters and of `build`.
+    vis: Option<syn::Visibility>,
+    /// `#[builder(name = "...")]`: the builder's name instead of
+    /// `{Input}Builder`.
+    name: Option<syn::Ident>,
+    /// `#[builder(derive(...))]`: traits derived for the builder.
+    derives: Vec<syn::Path>,
+    /// `#[builder(struct_attr(...))]`: attributes put on the builder as is.
+    struct_attrs: Vec<syn::Meta>,
+    /// `#[builder(env_prefix = "...")]`: every field is read from the
+    /// environment variable named by the prefix and the field in uppercase.
+    env_prefix: Option<syn::LitStr>,
+    /// `#[builder(cli)]`: the builder can be filled from command-line
+    /// arguments, see [`cli`].
+    cli: Option<syn::Path>,
+}
+
+/// `#[builder(build_fn(...))]`: how the method that builds the value is
+/// generated.
+#[derive(Default)]
+struct BuildFnOptions {
+    /// `validate = "path"`: a function that is given the builder before the
+    /// value is built, and returns `Result<(), E>` where `E: Into<String>`.
+    validate: Option<syn::Path>,
+    /// `name = "..."`: the method's name instead of `build`.
+    name: Option<syn::Ident>,
+    /// `private`: the method is left private, so that it can be wrapped in a
+    /// hand-written public one.
+    private: bool,
+}
+
+impl BuildFnOptions {
+    /// Parses the contents of `build_fn(...)`.
+    fn parse(&mut self, meta: &syn::meta::ParseNestedMeta, errors: &mut Errors) -> syn::Result<()> {
+        attr::parse_nested(meta, errors, |meta, _| {
+            if meta.path.is_ident("validate") {
+                self.validate = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("name") {
+                self.name = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("private") {
+                self.private = true;
+            } else {
+                return Err(attr::unknown_option(meta, &["validate", "name", "private"]));
+            }
+            Ok(())
+        })
+    }
+
+    /// The visibility and name of the method, where `vis` is the visibility
+    /// it has unless it is `private`.
+    fn signature(&self, vis: syn::Visibility) -> (TokenStream2, syn::Ident) {
+        let vis = if self.private {
+            quote! {}
+        } else {
+            quote! { #vis }
+        };
+        let name = self.name.clone().unwrap_or_else(|| format_ident!("build"));
+        (vis, name)
+    }
+}
+
+/// How the builder's methods take the builder, from
+/// `#[builder(pattern = "...")]`.
+#[derive(Default, Clone, Copy, PartialEq)]
+enum Pattern {
+    /// Setters take and return `&mut self`, and `build` clones the values
+    /// out of the builder.
+    #[default]
+    Mutable,
+    /// Setters and `build` take `self` by value, so no field has to be
+    /// `Clone`.
+    Owned,
+    /// Setters take `&self` and return an updated copy of the builder.
+    Immutable,
+}
+
+/// What `merge` does with a collection from `#[builder(each = "...")]` that
+/// is set in both builders, from `#[builder(merge = "...")]`.
+#[derive(Default, Clone, Copy, PartialEq)]
+enum Merge {
+    /// The elements of the other builder are added to those of this one.
+    #[default]
+    Append,
+    /// The collection of the other builder replaces the one of this one.
+    Replace,
+}
+
+impl Pattern {
+    /// How the builder's methods take the builder, and the type that setters
+    /// return it as.
+    fn receiver(self) -> (TokenStream2, TokenStream2) {
+        match self {
+            Pattern::Mutable => (quote! { &mut self }, quote! { &mut Self }),
+            Pattern::Owned => (quote! { self }, quote! { Self }),
+            Pattern::Immutable => (quote! { &self }, quote! { Self }),
+        }
+    }
+}
+
+impl BuilderOptions {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut options = BuilderOptions::default();
+        let mut errors = Errors::default();
+        attr::parse_builder_attrs(attrs, &mut errors, |meta, errors| {
+            if meta.path.is_ident("typestate") {
+                options.typestate = true;
+            } else if meta.path.is_ident("default") {
+                options.default = Some(meta.path.clone());
+            } else if meta.path.is_ident("no_std") {
+                options.no_std = true;
+            } else if meta.path.is_ident("no_from") {
+                options.no_from = true;
+            } else if meta.path.is_ident("try_setter") {
+                options.setter.try_setter = true;
+            } else if meta.path.is_ident("pattern") {
+                let s: syn::LitStr = meta.value()?.parse()?;
+                options.pattern = match s.value().as_str() {
+                    "mutable" => Pattern::Mutable,
+                    "owned" => Pattern::Owned,
+                    "immutable" => Pattern::Immutable,
+                    _ => {
+                        return Err(syn::Error::new(
+                            s.span(),
+                            "expected `\"mutable\"`, `\"owned\"` or `\"immutable\"`",
+                        ))
+                    }
+                };
+                options.pattern_span = Some(s.span());
+            } else if meta.path.is_ident("setter") {
+                options.setter.parse(meta, errors)?;
+                if let Some(path) = options.setter.field_only() {
+                    return Err(syn::Error::new(
+                        path.span(),
+                        format!(
+                            "`setter({})` can only be given on a field",
+                            path.get_ident().unwrap(),
+                        ),
+                    ));
+                }
+            } else if meta.path.is_ident("build_fn") {
+                options.build_fn.parse(meta, errors)?;
+            } else if meta.path.is_ident("merge_fn") {
+                options.merge_fn = Some(meta.path.clone());
+            } else if meta.path.is_ident("vis") {
+                options.vis = Some(attr::parse_vis(meta)?);
+            } else if meta.path.is_ident("name") {
+                options.name = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("derive") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let derives =
+                    content.parse_terminated(syn::Path::parse_mod_style, syn::Token![,])?;
+                options.derives.extend(derives);
+            } else if meta.path.is_ident("struct_attr") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                options.struct_attrs.push(content.parse()?);
+            } else if meta.path.is_ident("cli") {
+                options.cli = Some(meta.path.clone());
+            } else if meta.path.is_ident("env_prefix") {
+                options.env_prefix = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("error") {
+                options.error = Some(attr::parse_str(meta)?);
+            } else {
+                return Err(attr::unknown_option(
+                    meta,
+                    &[
+                        "typestate",
+                        "default",
+                        "no_std",
+                        "no_from",
+                        "error",
+                        "pattern",
+                        "setter",
+                        "try_setter",
+                        "build_fn",
+                        "merge_fn",
+                        "vis",
+                        "name",
+                        "derive",
+                        "struct_attr",
+                        "env_prefix",
+                        "cli",
+                    ],
+                ));
+            }
+            Ok(())
+        });
+        if let Some(prefix) = &options.env_prefix {
+            if let Some(message) = options.fill_unsupported("the environment") {
+                errors.push(syn::Error::new(prefix.span(), message));
+            }
+        }
+        if let Some(cli) = &options.cli {
+            if let Some(message) = options.fill_unsupported("command-line arguments") {
+                errors.push(syn::Error::new(cli.span(), message));
+            }
+        }
+        if let Some(span) = options.pattern_span {
+            if options.typestate && options.pattern != Pattern::Owned {
+                errors.push(syn::Error::new(
+                    span,
+                    "`typestate` builders always take `self` by value",
+                ));
+            }
+        }
+        if let (true, Some(merge_fn)) = (options.typestate, &options.merge_fn) {
+            errors.push(syn::Error::new(
+                merge_fn.span(),
+                "`typestate` builders cannot be merged, which fields they set is part of their type",
+            ));
+        }
+        errors.finish()?;
+        Ok(options)
+    }
+
+    /// Why the builder cannot be filled from `source` at run time, if it
+    /// cannot.
+    fn fill_unsupported(&self, source: &str) -> Option<String> {
+        if self.typestate {
+            Some(format!(
+                "`typestate` builders cannot be filled from {}, which fields it sets is only \
+                 known at run time",
+                source
+            ))
+        } else if self.no_std {
+            Some(format!(
+                "reading {} needs `std`, which `no_std` builders do not use",
+                source
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// The visibility of the builder's methods, `pub` unless given.
+    fn method_vis(&self) -> syn::Visibility {
+        self.vis.clone().unwrap_or_else(|| syn::parse_quote!(pub))
+    }
+
+    /// The attributes of the builder struct from `derive(...)` and
+    /// `struct_attr(...)`.
+    fn builder_attrs(&self) -> TokenStream2 {
+        let derives = &self.derives;
+        let derive = (!derives.is_empty()).then(|| quote! { #[derive(#(#derives),*)] });
+        let struct_attrs = &self.struct_attrs;
+        quote! {
+            #derive
+            #(#[#struct_attrs])*
+        }
+    }
+
+    /// The crate that allocating types such as `String` are taken from.
+    fn alloc_crate(&self) -> TokenStream2 {
+        if self.no_std {
+            quote! { ::alloc }
+        } else {
+            quote! { ::std }
+        }
+    }
+}
+
+/// Where the value of a field that was never set comes from.
+enum FieldDefault {
+    /// `#[builder(default)]`: the field type's `Default` impl.
+    Trait,
+    /// `#[builder(default = "...")]`: an arbitrary expression.
+    Expr(syn::Expr),
+    /// `#[builder(default)]` on the input type: the field's value in the
+    /// input type's `Default` impl, which `build` keeps in `__default`.
+    Input,
+}
+
+/// A field of the input as the builder sees it.
+struct BuilderField {
+    /// How the field is addressed in the built value. Tuple fields are
+    /// addressed by position.
+    member: syn::Member,
+    /// Name of the builder's storage for the field and of its setter. Tuple
+    /// fields get positional setters `_0`, `_1`, ... unless renamed with
+    /// `#[builder(name = "...")]`.
+    ident: syn::Ident,
+    ty: syn::Type,
+    each: Option<Each>,
+    default: Option<FieldDefault>,
+    setter: SetterOptions,
+    /// The environment variable the field is read from.
+    env: Option<String>,
+    /// The first line of the field's doc comment.
+    doc: Option<String>,
+    /// `#[builder(sub_builder)]`: the builder of the field's type, which the
+    /// builder holds in place of the field and builds in `build`. Its setters
+    /// and `build` have to take it by `&mut self`, as they do by default. Its
+    /// error is kept in the outer error, so a custom `error` type of the
+    /// field's builder has to implement `derive_builder::BuildError`, along
+    /// with the traits that the generated errors derive.
+    sub_builder: Option<syn::Type>,
+    /// `#[builder(group = "...")]`, with the group's rule if it is given on
+    /// this field.
+    group: Option<group::FieldGroup>,
+    /// `#[builder(required_if = "...")]`: the field that, once set, requires
+    /// this one to be set too.
+    required_if: Option<syn::Ident>,
+    /// The checks from `#[builder(validate(...))]`.
+    validators: Vec<validate::Validator>,
+    /// `#[builder(start)]`: the field is a parameter of the function that
+    /// creates the builder instead of having a setter, so it is always set.
+    start: bool,
+}
+
+impl BuilderField {
+    fn parse(index: usize, field: syn::Field, options: &BuilderOptions) -> syn::Result<Self> {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(index)),
+        };
+        let mut agg: Option<syn::Ident> = None;
+        let mut name = None;
+        let mut default = None;
+        let mut setter = SetterArgs::default();
+        let mut vis = None;
+        let mut merge = None;
+        let mut env: Option<syn::LitStr> = None;
+        let mut sub_builder = None;
+        let mut group_name: Option<syn::Ident> = None;
+        let mut group_rule = None;
+        let mut required_if: Option<syn::Ident> = None;
+        let mut validators = Vec::new();
+        let mut validate_span = None;
+        let mut start = None;
+        let mut errors = Errors::default();
+
+        attr::parse_builder_attrs(&field.attrs, &mut errors, |meta, errors| {
+            if meta.path.is_ident("each") {
+                agg = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("name") {
+                name = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("setter") {
+                setter.parse(meta, errors)?;
+            } else if meta.path.is_ident("try_setter") {
+                setter.try_setter = true;
+            } else if meta.path.is_ident("vis") {
+                vis = Some(attr::parse_vis(meta)?);
+            } else if meta.path.is_ident("env") {
+                env = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("sub_builder") {
+                sub_builder = Some(if meta.input.peek(syn::Token![=]) {
+                    (meta.path.clone(), Some(attr::parse_str(meta)?))
+                } else {
+                    (meta.path.clone(), None)
+                });
+            } else if meta.path.is_ident("group") {
+                group_name = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("exactly_one") {
+                group_rule = Some((group::Rule::ExactlyOne, meta.path.span()));
+            } else if meta.path.is_ident("at_most_one") {
+                group_rule = Some((group::Rule::AtMostOne, meta.path.span()));
+            } else if meta.path.is_ident("required_if") {
+                required_if = Some(attr::parse_str(meta)?);
+            } else if meta.path.is_ident("start") {
+                start = Some(meta.path.span());
+            } else if meta.path.is_ident("validate") {
+                validate_span = Some(meta.path.span());
+                validate::Validator::parse_all(meta, &mut validators, errors)?;
+            } else if meta.path.is_ident("merge") {
+                let s: syn::LitStr = meta.value()?.parse()?;
+                let mode = match s.value().as_str() {
+                    "append" => Merge::Append,
+                    "replace" => Merge::Replace,
+                    _ => {
+                        return Err(syn::Error::new(
+                            s.span(),
+                            "expected `\"append\"` or `\"replace\"`",
+                        ))
+                    }
+                };
+                merge = Some((mode, s.span()));
+            } else if meta.path.is_ident("default") {
+                default = Some(if meta.input.peek(syn::Token![=]) {
+                    // Parsing from the literal gives the expression the
+                    // literal's span, so that errors in it point at the
+                    // attribute.
+                    FieldDefault::Expr(attr::parse_str(meta)?)
+                } else {
+                    FieldDefault::Trait
+                });
+            } else {
+                return Err(attr::unknown_option(
+                    meta,
+                    &[
+                        "each",
+                        "name",
+                        "setter",
+                        "try_setter",
+                        "vis",
+                        "merge",
+                        "env",
+                        "sub_builder",
+                        "group",
+                        "exactly_one",
+                        "at_most_one",
+                        "required_if",
+                        "validate",
+                        "start",
+                        "default",
+                    ],
+                ));
+            }
+            Ok(())
+        });
+
+        let ident = match (name, field.ident) {
+            (Some(name), _) => name,
+            (None, Some(ident)) => ident,
+            (None, None) => format_ident!("_{}", index),
+        };
+        if default.is_none() && options.default.is_some() {
+            default = Some(FieldDefault::Input);
+        }
+        // A switch that is not given is off.
+        if options.cli.is_some() && cli::is_bool(&field.ty) && default.is_none() {
+            default = Some(FieldDefault::Trait);
+        }
+        if setter.skip().is_some() {
+            if let Some(each) = &agg {
+                errors.push(syn::Error::new(
+                    each.span(),
+                    "`setter(skip)` fields have no setters, `each` cannot be given",
+                ));
+            }
+            if default.is_none() {
+                default = Some(FieldDefault::Trait);
+            }
+        }
+
+        let each = agg.and_then(|name| {
+            let Some(item) = EachItem::of_collection(&field.ty) else {
+                errors.push(syn::Error::new(
+                    field.ty.span(),
+                    "`builder(each = \"...\")` needs a collection type with its element type \
+                     as a type argument, such as `Vec<T>` or `HashMap<K, V>`",
+                ));
+                return None;
+            };
+            // A collection that nothing was added to is empty.
+            if default.is_none() {
+                default = Some(FieldDefault::Trait);
+            }
+            Some(Each {
+                name,
+                item,
+                merge: merge.map_or(Merge::default(), |(mode, _)| mode),
+            })
+        });
+        if let (None, Some((_, span))) = (&options.merge_fn, merge) {
+            errors.push(syn::Error::new(
+                span,
+                "`merge` only applies to builders with `#[builder(merge_fn)]` on the input type",
+            ));
+        } else if let (None, Some((_, span))) = (&each, merge) {
+            errors.push(syn::Error::new(
+                span,
+                "`merge` only applies to fields with `each = \"...\"`, other fields are \
+                 replaced by `merge` when they are set",
+            ));
+        }
+        let sub_builder = sub_builder.and_then(|(path, builder_ty)| {
+            let conflict = if each.is_some() {
+                Some("`each = \"...\"`")
+            } else if setter.skip().is_some() {
+                Some("`setter(skip)`")
+            } else if default.is_some() && options.default.is_none() {
+                Some("`default`")
+            } else if env.is_some() {
+                Some("`env = \"...\"`")
+            } else {
+                None
+            };
+            if let Some(conflict) = conflict {
+                errors.push(syn::Error::new(
+                    path.span(),
+                    format!("`sub_builder` fields cannot have {}", conflict),
+                ));
+                return None;
+            }
+            let builder_ty = builder_ty.or_else(|| builder_of(&field.ty));
+            if builder_ty.is_none() {
+                errors.push(syn::Error::new(
+                    field.ty.span(),
+                    "`sub_builder` needs the name of a type that derives `Builder`, \
+                     or the builder type given as `sub_builder = \"...\"`",
+                ));
+            }
+            builder_ty
+        });
+        // Only fields that may be left unset can be left out by a rule.
+        let optional = default.is_some() || is_option(&field.ty);
+        let group = match (group_name, group_rule) {
+            (Some(name), rule) => {
+                let conflict = if each.is_some() {
+                    Some("fields with `each = \"...\"` cannot be in a group")
+                } else if setter.skip().is_some() {
+                    Some("`setter(skip)` fields cannot be in a group")
+                } else if sub_builder.is_some() {
+                    Some("`sub_builder` fields cannot be in a group")
+                } else if !optional {
+                    Some("fields in a group have to be `Option` or have a `default`, for when another field of the group is set")
+                } else {
+                    None
+                };
+                if let Some(message) = conflict {
+                    errors.push(syn::Error::new(name.span(), message));
+                }
+                Some(group::FieldGroup { name, rule })
+            }
+            (None, Some((_, span))) => {
+                errors.push(syn::Error::new(
+                    span,
+                    "`exactly_one` and `at_most_one` need the group they apply to, given with `group = \"...\"`",
+                ));
+                None
+            }
+            (None, None) => None,
+        };
+        if let Some(other) = &required_if {
+            let conflict = if setter.skip().is_some() {
+                Some("`setter(skip)` fields cannot be `required_if`")
+            } else if sub_builder.is_some() {
+                Some("`sub_builder` fields cannot be `required_if`")
+            } else if !optional {
+                Some("the field is always required, `required_if` only applies to fields that are `Option` or have a `default`")
+            } else {
+                None
+            };
+            if let Some(message) = conflict {
+                errors.push(syn::Error::new(other.span(), message));
+            }
+        }
+        if let Some(span) = start {
+            let conflict = if each.is_some() {
+                Some("fields with `each = \"...\"`")
+            } else if setter.skip().is_some() {
+                Some("`setter(skip)` fields")
+            } else if sub_builder.is_some() {
+                Some("`sub_builder` fields")
+            } else if group.is_some() {
+                Some("fields in a group")
+            } else if default.is_some() && options.default.is_none() {
+                Some("fields with a `default`")
+            } else if env.is_some() {
+                Some("fields read from the environment")
+            } else if options.cli.is_some() {
+                Some("`cli` builders, whose `parse_args` creates the builder,")
+            } else {
+                None
+            };
+            if let Some(conflict) = conflict {
+                errors.push(syn::Error::new(
+                    span,
+                    format!("{} cannot be `start` fields", conflict),
+                ));
+            }
+        }
+        if let Some(span) = validate_span {
+            let conflict = if setter.skip().is_some() {
+                Some("`setter(skip)` fields are never set, there is nothing to `validate`")
+            } else if sub_builder.is_some() {
+                Some("`sub_builder` fields cannot be validated, validate the fields of their builder instead")
+            } else {
+                None
+            };
+            if let Some(message) = conflict {
+                errors.push(syn::Error::new(span, message));
+            }
+        }
+        let env = match (env, &options.env_prefix) {
+            (Some(env), _) => {
+                let unsupported = if each.is_some() {
+                    Some("fields with `each = \"...\"` cannot be read from the environment".into())
+                } else if setter.skip().is_some() {
+                    Some("`setter(skip)` fields cannot be read from the environment".into())
+                } else {
+                    options.fill_unsupported("the environment")
+                };
+                if let Some(message) = unsupported {
+                    errors.push(syn::Error::new(env.span(), message));
+                }
+                Some(env.value())
+            }
+            // Collections have no single value to parse, so `env_prefix`
+            // leaves them out along with skipped fields.
+            (None, Some(prefix))
+                if each.is_none()
+                    && setter.skip().is_none()
+                    && sub_builder.is_none()
+                    && start.is_none() =>
+            {
+                Some(format!(
+                    "{}{}",
+                    prefix.value(),
+                    ident.unraw().to_string().to_uppercase(),
+                ))
+            }
+            (None, _) => None,
+        };
+        errors.finish()?;
+
+        Ok(BuilderField {
+            member,
+            ident,
+            ty: field.ty,
+            each,
+            default,
+            setter: setter.resolve(&options.setter, vis.unwrap_or_else(|| options.method_vis())),
+            env,
+            doc: doc_summary(&field.attrs),
+            sub_builder,
+            group,
+            required_if,
+            validators,
+            start: start.is_some(),
+        })
+    }
+
+    /// For `Option`, the inner generic argument type, which is what the
+    /// setter takes.
+    fn option_inner(&self) -> Option<&syn::Type> {
+        if let syn::Type::Path(p) = &self.ty {
+            if let Some(path) = p.path.segments.first() {
+                if path.ident == "Option" {
+                    if let syn::PathArguments::AngleBracketed(args) = &path.arguments {
+                        if let Some(syn::GenericArgument::Type(inner_t)) = args.args.first() {
+                            return Some(inner_t);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether the builder has storage for the field, which is the case
+    /// unless it is `setter(skip)`.
+    fn is_stored(&self) -> bool {
+        !self.setter.skip
+    }
+
+    /// The value `build` gives the field, where `stored` is the builder's
+    /// `Option` holding what the field was set to, if it is stored.
+    fn build_value(&self, stored: TokenStream2, target: &BuilderTarget) -> TokenStream2 {
+        if self.sub_builder.is_some() {
+            // The error of the field's builder is kept in the variant of the
+            // field, which shows it with the field's path.
+            let error_name = target.error_name();
+            let field_error_name = target.field_error_name();
+            let variant = error::field_variant(self);
+            let alloc = target.options.alloc_crate();
+            return quote! {
+                match #stored.build() {
+                    ::core::result::Result::Ok(value) => value,
+                    ::core::result::Result::Err(err) => {
+                        return ::core::result::Result::Err(::core::convert::From::from(
+                            #error_name::SubBuilder(#field_error_name::#variant(
+                                #alloc::boxed::Box::new(err),
+                            )),
+                        ));
+                    }
+                }
+            };
+        }
+        if self.start {
+            return stored;
+        }
+        let Some(default) = &self.default else {
+            if is_option(&self.ty) {
+                return stored;
+            }
+            let error_name = target.error_name();
+            let field_name = self.ident.to_string();
+            return quote! {
+                #stored.ok_or(#error_name::MissingField(#field_name))?
+            };
+        };
+        let default = match default {
+            FieldDefault::Trait => {
+                quote_spanned! {self.ty.span()=> ::core::default::Default::default() }
+            }
+            FieldDefault::Expr(expr) => quote! { #expr },
+            FieldDefault::Input => {
+                let member = &self.member;
+                quote! { __default.#member }
+            }
+        };
+        if !self.is_stored() {
+            return default;
+        }
+        // The storage of an `Option` field is that `Option` itself.
+        let value = if is_option(&self.ty) {
+            quote! { Some(value) }
+        } else {
+            quote! { value }
+        };
+        quote! {
+            match #stored {
+                Some(value) => #value,
+                None => #default,
+            }
+        }
+    }
+
+    /// The type of the builder's storage for the field.
+    fn storage_ty(&self) -> TokenStream2 {
+        let ty = &self.ty;
+        if self.start {
+            return quote! { #ty };
+        }
+        match &self.sub_builder {
+            Some(builder_ty) => quote! { #builder_ty },
+            // The storage of an `Option` field is that `Option` itself.
+            None if is_option(ty) => quote! { #ty },
+            None => quote! { ::core::option::Option<#ty> },
+        }
+    }
+
+    /// The storage of the field in a new builder.
+    fn initial(&self) -> TokenStream2 {
+        let ty = &self.ty;
+        if self.start {
+            return self.start_param().1;
+        }
+        match &self.sub_builder {
+            Some(_) => quote! { <#ty>::builder() },
+            None => quote! { None },
+        }
+    }
+
+    /// The parameter of the function that creates the builder for a `start`
+    /// field, and the value it stores.
+    fn start_param(&self) -> (TokenStream2, TokenStream2) {
+        self.setter.param(&self.ident.to_string(), &self.ty)
+    }
+
+    /// The value the field is set to in the builder, as an `Option<&T>`
+    /// where `T` is the field type or the inner type of an `Option` field.
+    fn set_value(&self, target: &BuilderTarget) -> TokenStream2 {
+        let field_id = &self.ident;
+        if (target.options.typestate && self.is_required()) || (self.start && !is_option(&self.ty))
+        {
+            let value = if self.start {
+                quote! { &self.#field_id }
+            } else {
+                quote! { &self.#field_id.0 }
+            };
+            quote! { ::core::option::Option::Some(#value) }
+        } else {
+            quote! { self.#field_id.as_ref() }
+        }
+    }
+
+    /// The builder's storage for the field set to `value`.
+    fn store(&self, value: TokenStream2) -> TokenStream2 {
+        if self.start {
+            value
+        } else if self.sub_builder.is_some() {
+            quote! { ::core::convert::From::from(#value) }
+        } else if is_option(&self.ty) {
+            value
+        } else {
+            quote! { Some(#value) }
+        }
+    }
+
+    /// The statement of `merge` that takes the field over from the builder
+    /// `other`, if it is set there.
+    fn merge_from(&self, other: TokenStream2) -> TokenStream2 {
+        let field_id = &self.ident;
+        if self.sub_builder.is_some() {
+            return quote! {
+                self.#field_id.merge(#other.#field_id);
+            };
+        }
+        if self.start {
+            return quote! {
+                self.#field_id = #other.#field_id;
+            };
+        }
+        match &self.each {
+            Some(each) if each.merge == Merge::Append => quote! {
+                if let Some(other) = #other.#field_id {
+                    ::core::iter::Extend::extend(
+                        self.#field_id.get_or_insert_with(::core::default::Default::default),
+                        other,
+                    );
+                }
+            },
+            _ => quote! {
+                if #other.#field_id.is_some() {
+                    self.#field_id = #other.#field_id;
+                }
+            },
+        }
+    }
+
+    /// Whether `build` fails when the field was never set.
+    fn is_required(&self) -> bool {
+        self.default.is_none() && !is_option(&self.ty) && self.sub_builder.is_none() && !self.start
+    }
+}
+
+/// What a builder produces and how it is reached from the input type.
+struct BuilderTarget<'a> {
+    /// The input type, which `build` returns.
+    output: &'a syn::Ident,
+    /// Generic parameters of `output`, which the builder takes over.
+    generics: &'a syn::Generics,
+    /// Either the struct itself or a path to one of the enum's variants,
+    /// evaluated as `path { field: value, ... }` by `build`.
+    path: TokenStream2,
+    builder_name: syn::Ident,
+    /// Visibility of the input type, which the builder and its error take
+    /// over.
+    vis: &'a syn::Visibility,
+    /// Function on `output` that creates the builder.
+    builder_fn: syn::Ident,
+    /// Whether the builder builds one variant of an enum, whose other
+    /// variants cannot be turned back into the builder.
+    is_variant: bool,
+    options: &'a BuilderOptions,
+    /// The groups among the fields, from `#[builder(group = "...")]`.
+    groups: Vec<group::Group>,
+}
+
+impl BuilderTarget<'_> {
+    /// Name of the error enum generated for the builder.
+    fn error_name(&self) -> syn::Ident {
+        format_ident!("{}Error", self.builder_name)
+    }
+
+    /// Name of the enum of the errors of `sub_builder` fields.
+    fn field_error_name(&self) -> syn::Ident {
+        format_ident!("{}FieldError", self.builder_name)
+    }
+
+    /// The error type returned by `build`.
+    fn build_error(&self) -> TokenStream2 {
+        match &self.options.error {
+            Some(error) => quote! { #error },
+            None => {
+                let error_name = self.error_name();
+                quote! { #error_name }
+            }
+        }
+    }
+}
+
+/// The pieces of generated code for one builder: the constructor that goes
+/// into the `impl` block of the input type, and the builder struct with its
+/// own `impl` blocks.
+struct Builder {
+    constructor: TokenStream2,
+    items: TokenStream2,
+}
+
+/// Generates a builder for `target` that tracks its fields at run time. How
+/// its setters and `build` take the builder depends on the builder's
+/// [`Pattern`]; typestate builders are generated by [`typestate`] instead.
+fn expand_builder(fields: &[BuilderField], target: &BuilderTarget) -> Builder {
+    let BuilderTarget {
+        output,
+        generics,
+        path,
+        builder_name,
+        vis,
+        builder_fn,
+        options,
+        ..
+    } = target;
+
+    let mut qbuilder_field_declarations = Vec::new();
+    let mut qbuilder_method_definitions = Vec::new();
+    let mut qbuilder_merge_statements = Vec::new();
+    let mut qbuilder_field_assignments = Vec::new();
+    let mut qbuilder_build_assignments = Vec::new();
+    // Unless the builder is owned, `build` clones every field, which for
+    // fields whose type involves a type parameter only holds under an extra
+    // bound. Immutable setters clone the whole builder under the same bounds.
+    // A mutable builder builds its sub-builders in place instead.
+    let qbuilder_clone_bounds: Vec<_> = fields
+        .iter()
+        .filter(|field| field.is_stored())
+        .filter(|field| field.sub_builder.is_none() || options.pattern != Pattern::Mutable)
+        .map(|field| {
+            let ty = field.storage_ty();
+            quote! { #ty: ::core::clone::Clone, }
+        })
+        .collect();
+    let phantom = phantom_field(target);
+
+    let setter = |name: &syn::Ident, args: TokenStream2, assign: TokenStream2| match options.pattern
+    {
+        Pattern::Mutable => setter::mutable_setter(name, args, assign),
+        Pattern::Owned => setter::owned_setter(name, args, assign),
+        Pattern::Immutable => {
+            let field_ids = fields
+                .iter()
+                .filter(|field| field.is_stored())
+                .map(|field| &field.ident);
+            let phantom = phantom
+                .as_ref()
+                .map(|_| quote! { __phantom: self.__phantom, });
+            quote! {
+                fn #name(&self, #args) -> Self
+                where
+                    #(#qbuilder_clone_bounds)*
+                {
+                    let mut new = #builder_name {
+                        #(#field_ids: ::core::clone::Clone::clone(&self.#field_ids),)*
+                        #phantom
+                    };
+                    let builder = &mut new;
+                    #assign
+                    new
+                }
+            }
+        }
+    };
+
+    for field in fields {
+        let field_id = &field.ident;
+        let member = &field.member;
+
+        if !field.is_stored() {
+            let value = field.build_value(quote! {}, target);
+            qbuilder_build_assignments.push(quote! {
+                #member: #value,
+            });
+            continue;
+        }
+
+        let field_ty = field.storage_ty();
+        qbuilder_field_declarations.push(quote! {
+            #field_id: #field_ty,
+        });
+
+        qbuilder_method_definitions.extend(setter::field_setters(field, options.pattern, &setter));
+        qbuilder_merge_statements.push(field.merge_from(quote! { other }));
+
+        let initial = field.initial();
+        qbuilder_field_assignments.push(quote! {
+            #field_id: #initial,
+        });
+
+        // A sub-builder is built in place, but has to be taken out of an
+        // owned builder and copied out of an immutable one first.
+        let stored = match (options.pattern, &field.sub_builder) {
+            (Pattern::Owned, Some(_)) => quote! { { self.#field_id } },
+            (Pattern::Mutable, Some(_)) => quote! { self.#field_id },
+            (Pattern::Immutable, Some(_)) => {
+                quote! { ::core::clone::Clone::clone(&self.#field_id) }
+            }
+            (Pattern::Owned, None) => quote! { self.#field_id },
+            (_, None) => quote! { self.#field_id.clone() },
+        };
+        let value = field.build_value(stored, target);
+        qbuilder_build_assignments.push(quote! {
+            #member: #value,
+        });
+    }
+
+    if let Some((declaration, assignment)) = phantom {
+        qbuilder_field_declarations.push(declaration);
+        qbuilder_field_assignments.push(assignment);
+    }
+
+    let (build_receiver, _) = options.pattern.receiver();
+    let build_clone_bounds: &[TokenStream2] = if options.pattern == Pattern::Owned {
+        &[]
+    } else {
+        &qbuilder_clone_bounds
+    };
+    let build_bounds = (!build_clone_bounds.is_empty()).then(|| {
+        quote! {
+            where
+                #(#build_clone_bounds)*
+        }
+    });
+
+    let build_preamble = build_preamble(fields, target);
+    let (build_vis, build_fn) = options.build_fn.signature(options.method_vis());
+    let build_error = target.build_error();
+    let merge_fn = options.merge_fn.as_ref().map(|_| {
+        let merge_vis = options.method_vis();
+        quote! {
+            /// Takes over the fields that are set in `other`. Collections
+            /// are appended to, unless their field is `merge = "replace"`.
+            #merge_vis fn merge(&mut self, other: Self) -> &mut Self {
+                #(#qbuilder_merge_statements)*
+                self
+            }
+        }
+    });
+    let error_items = error::expand_error(fields, target);
+    let env_methods = env::expand_env(fields, target);
+    let cli_methods = cli::expand_cli(fields, target);
+    let missing_fields = expand_missing_fields(fields, target);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let builder_ty = quote! { #builder_name #ty_generics };
+    let (to_builder, from_output) =
+        expand_to_builder(fields, target, &builder_ty, quote! {}, |field, value| {
+            field.store(value)
+        });
+
+    let buildable = traits::expand_buildable(fields, target, &builder_ty);
+    let builder_impl = traits::expand_builder(target, generics, &builder_ty, build_clone_bounds);
+    let build_fn_error = traits::expand_build_fn_error(target, &builder_ty);
+
+    let start_params = start_params(fields);
+    let constructor = quote! {
+        #vis fn #builder_fn (#(#start_params),*) -> #builder_ty {
+            #builder_name {
+                #(#qbuilder_field_assignments)*
+            }
+        }
+
+        #to_builder
+    };
+
+    let builder_attrs = options.builder_attrs();
+    let items = quote! {
+        #builder_attrs
+        #vis struct #builder_name #generics #where_clause {
+            #(#qbuilder_field_declarations)*
+        }
+
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #build_vis fn #build_fn(#build_receiver) -> ::core::result::Result<#output #ty_generics, #build_error>
+            #build_bounds
+            {
+                #build_preamble
+                Ok(#path {
+                    #(#qbuilder_build_assignments)*
+                })
+            }
+
+            #merge_fn
+
+            #missing_fields
+
+            #env_methods
+
+            #cli_methods
+
+            #(#qbuilder_method_definitions)*
+        }
+
+        #error_items
+
+        #from_output
+
+        #buildable
+
+        #builder_impl
+
+        #build_fn_error
+    };
+
+    Builder { constructor, items }
+}
+
+/// The code that `build` runs before building the value: the check for
+/// missing fields, the checks of the rules across fields and of the fields'
+/// validators, the validation from `build_fn(validate = "...")`, and the
+/// binding of `__default`.
+fn build_preamble(fields: &[BuilderField], target: &BuilderTarget) -> TokenStream2 {
+    let options = target.options;
+    let validation = options.build_fn.validate.as_ref().map(|validate| {
+        let error_name = target.error_name();
+        // `build` takes the builder by reference unless it is owned.
+        let builder = if options.typestate || options.pattern == Pattern::Owned {
+            quote! { &self }
+        } else {
+            quote! { self }
+        };
+        quote! {
+            #validate(#builder).map_err(|err| #error_name::ValidationError(::core::convert::Into::into(err)))?;
+        }
+    });
+    let missing = missing_check(fields, target);
+    let checks = group::expand_checks(fields, target);
+    let validators = validate::expand_validators(fields, target);
+    // Every check runs, so that `build` reports all of the problems at once.
+    // The validation hook is only given builders that pass them.
+    let failures = (missing.is_some() || !checks.is_empty() || !validators.is_empty()).then(|| {
+        let error_name = target.error_name();
+        let alloc = options.alloc_crate();
+        quote! {
+            let mut failures: #alloc::vec::Vec<#error_name> = #alloc::vec::Vec::new();
+            #missing
+            #checks
+            #validators
+            if failures.len() > 1 {
+                return ::core::result::Result::Err(::core::convert::From::from(
+                    #error_name::Multiple(failures),
+                ));
+            }
+            if let ::core::option::Option::Some(failure) = failures.pop() {
+                return ::core::result::Result::Err(::core::convert::From::from(failure));
+            }
+        }
+    });
+    let default_binding = default_binding(fields, target);
+    quote! {
+        #failures
+        #validation
+        #default_binding
+    }
+}
+
+/// The parameters of the function that creates the builder, one for each
+/// `start` field.
+fn start_params(fields: &[BuilderField]) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .filter(|field| field.start)
+        .map(|field| field.start_param().0)
+        .collect()
+}
+
+/// `missing_fields`, which lists the required fields that are not set yet.
+/// Those of `sub_builder` fields are listed by their path, such as
+/// `tls.cert`. Typestate builders track them in their type instead.
+fn expand_missing_fields(fields: &[BuilderField], target: &BuilderTarget) -> TokenStream2 {
+    let alloc = target.options.alloc_crate();
+    let vis = target.options.method_vis();
+    let checks = fields.iter().filter_map(|field| {
+        let field_id = &field.ident;
+        let name = field.ident.unraw().to_string();
+        if field.sub_builder.is_some() {
+            Some(quote! {
+                for nested in self.#field_id.missing_fields() {
+                    missing.push(#alloc::format!("{}.{}", #name, nested));
+                }
+            })
+        } else if field.is_required() {
+            Some(quote! {
+                if self.#field_id.is_none() {
+                    missing.push(#alloc::string::ToString::to_string(#name));
+                }
+            })
+        } else {
+            None
+        }
+    });
+    quote! {
+        /// The names of the required fields that are not set yet, in the
+        /// order of their declaration. Those of `sub_builder` fields are
+        /// paths built at run time, such as `tls.cert`, hence `String`s
+        /// rather than `&'static str`s.
+        #vis fn missing_fields(&self) -> #alloc::vec::Vec<#alloc::string::String> {
+            #[allow(unused_mut)]
+            let mut missing = #alloc::vec::Vec::new();
+            #(#checks)*
+            missing
+        }
+    }
+}
+
+/// Errors for setters named like one of the builder's own methods or like
+/// each other, which would otherwise only show up as duplicate definitions
+/// in generated code.
+fn check_method_names(fields: &[BuilderField], target: &BuilderTarget, errors: &mut Errors) {
+    let options = target.options;
+    let (_, build_fn) = options.build_fn.signature(options.method_vis());
+    let mut methods = vec![build_fn];
+    if !options.typestate {
+        methods.push(format_ident!("missing_fields"));
+        if options.merge_fn.is_some() {
+            methods.push(format_ident!("merge"));
+        }
+        if env::uses_env(fields) {
+            methods.push(format_ident!("fill_from_env"));
+            if !fields.iter().any(|field| field.start) {
+                methods.push(format_ident!("from_env"));
+            }
+        }
+        if options.cli.is_some() {
+            methods.push(format_ident!("parse_args"));
+        }
+    }
+    let mut setters: Vec<syn::Ident> = Vec::new();
+    for field in fields {
+        for setter in setter::setter_names(field) {
+            let hint = if setter == field.ident {
+                ", rename it with `#[builder(name = \"...\")]`"
+            } else {
+                ""
+            };
+            let clash = if methods.iter().any(|method| setter.unraw() == *method) {
+                Some("a method of the builder")
+            } else if setters.iter().any(|other| setter.unraw() == other.unraw()) {
+                Some("another setter")
+            } else {
+                None
+            };
+            if let Some(clash) = clash {
+                errors.push(syn::Error::new(
+                    setter.span(),
+                    format!(
+                        "the setter `{}` has the same name as {}{}",
+                        setter.unraw(),
+                        clash,
+                        hint,
+                    ),
+                ));
+            }
+            setters.push(setter);
+        }
+    }
+}
+
+/// Errors for `sub_builder` fields whose builder depends on the generic
+/// parameters of the input. The error of their builder is part of the error
+/// enum, which has no parameters.
+fn check_sub_builders(fields: &[BuilderField], target: &BuilderTarget, errors: &mut Errors) {
+    for field in fields {
+        if let Some(sub_builder) = &field.sub_builder {
+            if uses_generics(quote! { #sub_builder }, target.generics) {
+                errors.push(syn::Error::new_spanned(
+                    &field.ty,
+                    "`sub_builder` fields cannot depend on the generic parameters of the input \
+                     type, the error of their builder is part of the builder's error, which has \
+                     none",
+                ));
+            }
+        }
+    }
+}
+
+/// Whether `tokens` mention a parameter of `generics`.
+fn uses_generics(tokens: TokenStream2, generics: &syn::Generics) -> bool {
+    let mut after_quote = false;
+    tokens.into_iter().any(|token| {
+        let lifetime = std::mem::replace(&mut after_quote, false);
+        match token {
+            proc_macro2::TokenTree::Group(group) => uses_generics(group.stream(), generics),
+            proc_macro2::TokenTree::Punct(punct) => {
+                after_quote = punct.as_char() == '\'';
+                false
+            }
+            proc_macro2::TokenTree::Ident(ident) => {
+                generics.params.iter().any(|param| match param {
+                    syn::GenericParam::Lifetime(param) => lifetime && param.lifetime.ident == ident,
+                    syn::GenericParam::Type(param) => !lifetime && param.ident == ident,
+                    syn::GenericParam::Const(param) => !lifetime && param.ident == ident,
+                })
+            }
+            proc_macro2::TokenTree::Literal(_) => false,
+        }
+    })
+}
+
+/// The check that adds an error with every required field that is not set,
+/// including those of `sub_builder` fields, to the failures of `build`.
+fn missing_check(fields: &[BuilderField], target: &BuilderTarget) -> Option<TokenStream2> {
+    let checked = fields
+        .iter()
+        .any(|field| field.is_required() || field.sub_builder.is_some());
+    if target.options.typestate || !checked {
+        return None;
+    }
+    let error_name = target.error_name();
+    // However many fields are missing, and wherever they are, they are
+    // reported the same way.
+    Some(quote! {
+        let missing = self.missing_fields();
+        if !missing.is_empty() {
+            failures.push(#error_name::MissingFields(missing));
+        }
+    })
+}
+
+/// The `__default` variable that `build` takes the values of unset fields
+/// from, for `#[builder(default)]` on the input type.
+fn default_binding(fields: &[BuilderField], target: &BuilderTarget) -> Option<TokenStream2> {
+    if !fields
+        .iter()
+        .any(|field| matches!(field.default, Some(FieldDefault::Input)))
+    {
+        return None;
+    }
+    let output = target.output;
+    let (_, ty_generics, _) = target.generics.split_for_impl();
+    Some(quote! {
+        let __default: #output #ty_generics = ::core::default::Default::default();
+    })
+}
+
+/// `From<Output> for Builder`, which fills a builder in with the fields of a
+/// value, and `to_builder`, which does the same for a reference to a value
+/// that is `Clone`. `builder_ty` is the builder type they produce, `extra`
+/// initializes whatever else the builder holds besides its fields, and
+/// `stored` gives the builder's storage for a field set to a value. Returns
+/// the method for the input type's `impl` block and the `From` impl.
+fn expand_to_builder(
+    fields: &[BuilderField],
+    target: &BuilderTarget,
+    builder_ty: &TokenStream2,
+    extra: TokenStream2,
+    stored: impl Fn(&BuilderField, TokenStream2) -> TokenStream2,
+) -> (TokenStream2, TokenStream2) {
+    if target.is_variant {
+        return (quote! {}, quote! {});
+    }
+    let BuilderTarget {
+        output,
+        generics,
+        builder_name,
+        vis,
+        ..
+    } = target;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let assignments = fields
+        .iter()
+        .filter(|field| field.is_stored())
+        .map(|field| {
+            let field_id = &field.ident;
+            let member = &field.member;
+            let value = stored(field, quote! { value.#member });
+            quote! { #field_id: #value, }
+        });
+    let phantom = phantom_field(target).map(|(_, assignment)| assignment);
+
+    // A bound on `Self` that does not hold is an error even where the method
+    // is never called, unless the bound is higher-ranked.
+    let method = quote! {
+        #vis fn to_builder(&self) -> #builder_ty
+        where
+            for<'__builder> Self: ::core::clone::Clone,
+        {
+            ::core::convert::From::from(::core::clone::Clone::clone(self))
+        }
+    };
+    let conversion = quote! {
+        impl #impl_generics ::core::convert::From<#output #ty_generics> for #builder_ty #where_clause {
+            fn from(value: #output #ty_generics) -> Self {
+                #builder_name {
+                    #(#assignments)*
+                    #extra
+                    #phantom
+                }
+            }
+        }
+    };
+    (method, conversion)
+}
+
+/// A variant's builder does not necessarily mention every parameter of the
+/// enum in its fields, so the parameters are anchored in a marker field.
+/// Returns the field's declaration and its initializer.
+fn phantom_field(target: &BuilderTarget) -> Option<(TokenStream2, TokenStream2)> {
+    if target.generics.params.is_empty() {
+        return None;
+    }
+    let output = target.output;
+    let (_, ty_generics, _) = target.generics.split_for_impl();
+    Some((
+        quote! {
+            __phantom: ::core::marker::PhantomData<fn() -> #output #ty_generics>,
+        },
+        quote! {
+            __phantom: ::core::marker::PhantomData,
+        },
+    ))
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    let mut errors = Errors::default();
+    let options = BuilderOptions::parse(&input.attrs).unwrap_or_else(|err| {
+        // The fields are parsed all the same, to report their errors too.
+        errors.push(err);
+        BuilderOptions::default()
+    });
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut targets = Vec::new();
+    let mut conversions = Vec::new();
+
+    match input.data {
+        syn::Data::Struct(data_struct) => {
+            // Newtypes can skip the builder altogether, unless their field
+            // has builder options that `From` would bypass.
+            if let syn::Fields::Unnamed(fields) = &data_struct.fields {
+                let plain = |field: &syn::Field| {
+                    !field
+                        .attrs
+                        .iter()
+                        .any(|attr| attr.path().is_ident("builder"))
+                };
+                if fields.unnamed.len() == 1 && plain(&fields.unnamed[0]) && !options.no_from {
+                    let inner_ty = &fields.unnamed[0].ty;
+                    conversions.push(quote! {
+                        impl #impl_generics ::core::convert::From<#inner_ty> for #struct_name #ty_generics #where_clause {
+                            fn from(inner: #inner_ty) -> Self {
+                                #struct_name(inner)
+                            }
+                        }
+                    });
+                }
+            }
+            let target = BuilderTarget {
+                output: struct_name,
+                generics: &input.generics,
+                path: quote! { #struct_name },
+                builder_name: options
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("{}Builder", struct_name)),
+                vis: &input.vis,
+                builder_fn: format_ident!("builder"),
+                is_variant: false,
+                options: &options,
+                groups: Vec::new(),
+            };
+            targets.push((target, data_struct.fields));
+        }
+        // Each variant with fields gets its own builder, e.g. `Source::File`
+        // is built through `Source::file_builder()` into a `SourceFileBuilder`.
+        syn::Data::Enum(data_enum) => {
+            if let Some(default) = &options.default {
+                errors.push(syn::Error::new(
+                    default.span(),
+                    "`builder(default)` on the input type is only supported on structs",
+                ));
+            }
+            if let Some(name) = &options.name {
+                errors.push(syn::Error::new(
+                    name.span(),
+                    "`builder(name = \"...\")` on the input type is only supported on structs, \
+                     the builder of each variant is named after it",
+                ));
+            }
+            for variant in data_enum.variants {
+                let variant_name = &variant.ident;
+                if let syn::Fields::Unit = variant.fields {
+                    errors.push(syn::Error::new(
+                        variant_name.span(),
+                        "`derive(Builder)` does not support unit variants, there are no fields to build",
+                    ));
+                    continue;
+                }
+                let target = BuilderTarget {
+                    output: struct_name,
+                    generics: &input.generics,
+                    path: quote! { #struct_name::#variant_name },
+                    builder_name: format_ident!("{}{}Builder", struct_name, variant_name),
+                    vis: &input.vis,
+                    builder_fn: format_ident!("{}_builder", snake_case(variant_name)),
+                    is_variant: true,
+                    options: &options,
+                    groups: Vec::new(),
+                };
+                targets.push((target, variant.fields));
+            }
+        }
+        syn::Data::Union(data_union) => errors.push(syn::Error::new(
+            data_union.union_token.span,
+            "`derive(Builder)` does not support unions",
+        )),
+    }
+
+    let mut constructors = Vec::new();
+    let mut items = Vec::new();
+    for (mut target, fields) in targets {
+        // Fields with errors are left out, the builder is only generated
+        // when there are no errors at all.
+        let fields: Vec<BuilderField> = fields
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, field)| {
+                BuilderField::parse(index, field, &options)
+                    .map_err(|err| errors.push(err))
+                    .ok()
+            })
+            .collect();
+        target.groups = group::resolve(&fields, &mut errors);
+        check_method_names(&fields, &target, &mut errors);
+        check_sub_builders(&fields, &target, &mut errors);
+        let builder = if options.typestate {
+            typestate::expand_builder(&fields, &target)
+        } else {
+            expand_builder(&fields, &target)
+        };
+        constructors.push(builder.constructor);
+        items.push(builder.items);
+    }
+    if let Err(err) = errors.finish() {
+        return err.to_compile_error().into();
+    }
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#constructors)*
+        }
+
+        #(#items)*
+
+        #(#conversions)*
+    }
+    .into()
+}
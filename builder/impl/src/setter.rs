@@ -0,0 +1,346 @@
+//! The setter methods of a builder.
+
+use crate::attr::{self, Errors};
+use crate::{is_option, BuilderField, Merge, Pattern};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+/// Wraps `assign`, code that updates the builder bound to `builder`, into a
+/// method called `name` that takes the arguments `args`. How the method
+/// receives and returns the builder depends on the builder's pattern.
+pub(crate) type Setter<'a> = dyn Fn(&syn::Ident, TokenStream2, TokenStream2) -> TokenStream2 + 'a;
+
+/// The [`Setter`] of builders whose setters take and return `&mut self`.
+pub(crate) fn mutable_setter(
+    name: &syn::Ident,
+    args: TokenStream2,
+    assign: TokenStream2,
+) -> TokenStream2 {
+    quote! {
+        fn #name(&mut self, #args) -> &mut Self {
+            let builder = &mut *self;
+            #assign
+            self
+        }
+    }
+}
+
+/// The [`Setter`] of builders whose setters take and return `self` by value.
+pub(crate) fn owned_setter(
+    name: &syn::Ident,
+    args: TokenStream2,
+    assign: TokenStream2,
+) -> TokenStream2 {
+    quote! {
+        fn #name(mut self, #args) -> Self {
+            let builder = &mut self;
+            #assign
+            self
+        }
+    }
+}
+
+/// `#[builder(setter(...))]` as written on a field, or on the input type
+/// where it sets the defaults for all fields.
+#[derive(Default)]
+pub(crate) struct SetterArgs {
+    into: Option<bool>,
+    strip_option: Option<bool>,
+    custom: Option<syn::Path>,
+    skip: Option<syn::Path>,
+    /// `#[builder(try_setter)]`, which is given outside of `setter(...)`.
+    pub(crate) try_setter: bool,
+}
+
+impl SetterArgs {
+    /// Parses the contents of `setter(...)`.
+    pub(crate) fn parse(
+        &mut self,
+        meta: &syn::meta::ParseNestedMeta,
+        errors: &mut Errors,
+    ) -> syn::Result<()> {
+        attr::parse_nested(meta, errors, |meta, _| {
+            if meta.path.is_ident("into") {
+                self.into = Some(parse_flag(meta)?);
+            } else if meta.path.is_ident("strip_option") {
+                self.strip_option = Some(parse_flag(meta)?);
+            } else if meta.path.is_ident("custom") {
+                self.custom = Some(meta.path.clone());
+            } else if meta.path.is_ident("skip") {
+                self.skip = Some(meta.path.clone());
+            } else {
+                return Err(attr::unknown_option(
+                    meta,
+                    &["into", "strip_option", "custom", "skip"],
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    /// Where `setter(custom)` or `setter(skip)` was given, which only make
+    /// sense on a field.
+    pub(crate) fn field_only(&self) -> Option<&syn::Path> {
+        self.custom.as_ref().or(self.skip.as_ref())
+    }
+
+    /// Where `setter(skip)` was given.
+    pub(crate) fn skip(&self) -> Option<&syn::Path> {
+        self.skip.as_ref()
+    }
+
+    /// The options of a field given `self` on the field and `defaults` on the
+    /// input type, where the setters have the visibility `vis`.
+    pub(crate) fn resolve(&self, defaults: &SetterArgs, vis: syn::Visibility) -> SetterOptions {
+        SetterOptions {
+            vis,
+            into: self.into.or(defaults.into).unwrap_or(false),
+            strip_option: self.strip_option.or(defaults.strip_option).unwrap_or(true),
+            custom: self.custom.is_some(),
+            skip: self.skip.is_some(),
+            try_setter: self.try_setter || defaults.try_setter,
+        }
+    }
+}
+
+/// Parses a flag written either as `flag` or as `flag = true`/`flag = false`.
+fn parse_flag(meta: &syn::meta::ParseNestedMeta) -> syn::Result<bool> {
+    if meta.input.peek(syn::Token![=]) {
+        let b: syn::LitBool = meta.value()?.parse()?;
+        Ok(b.value)
+    } else {
+        Ok(true)
+    }
+}
+
+/// How the setters of a field are generated.
+pub(crate) struct SetterOptions {
+    /// `vis = "..."` on the field or else on the input type, `pub` by
+    /// default.
+    pub(crate) vis: syn::Visibility,
+    /// `setter(into)`: setters take `impl Into<T>` instead of `T`.
+    pub(crate) into: bool,
+    /// `setter(strip_option)`, the default: setters of `Option<T>` fields
+    /// take `T`. With `strip_option = false` they take `Option<T>`.
+    pub(crate) strip_option: bool,
+    /// `setter(custom)`: no setters are generated, the storage is left for
+    /// hand-written ones.
+    pub(crate) custom: bool,
+    /// `setter(skip)`: the field has neither setters nor storage, `build`
+    /// always gives it its default.
+    pub(crate) skip: bool,
+    /// `#[builder(try_setter)]`: next to the setter there is a `try_` one
+    /// that takes `impl TryInto<T>`.
+    pub(crate) try_setter: bool,
+}
+
+impl SetterOptions {
+    /// The declaration of a setter parameter `name` of type `ty`, and the
+    /// expression that turns it into a `ty`.
+    pub(crate) fn param(&self, name: &str, ty: &syn::Type) -> (TokenStream2, TokenStream2) {
+        let name = format_ident!("{}", name);
+        if self.into {
+            (
+                quote! { #name: impl ::core::convert::Into<#ty> },
+                quote! { ::core::convert::Into::into(#name) },
+            )
+        } else {
+            (quote! { #name: #ty }, quote! { #name })
+        }
+    }
+}
+
+/// What `#[builder(each = "...")]` adds to the collection in a field: an
+/// element of a list or set such as `Vec<T>` or `HashSet<T>`, or a key and
+/// value of a map such as `HashMap<K, V>` or `BTreeMap<K, V>`.
+pub(crate) struct EachItem {
+    pub(crate) key: Option<syn::Type>,
+    pub(crate) value: syn::Type,
+}
+
+impl EachItem {
+    /// Reads the item type off the type arguments of a collection type.
+    /// Types named `...Map` with at least two type arguments are taken to be
+    /// maps, for any other type the first type argument is the element.
+    pub(crate) fn of_collection(ty: &syn::Type) -> Option<Self> {
+        let syn::Type::Path(p) = ty else {
+            return None;
+        };
+        let segment = p.path.segments.last()?;
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        let mut types = args.args.iter().filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        });
+        let first = types.next()?;
+        if segment.ident.to_string().ends_with("Map") {
+            if let Some(second) = types.next() {
+                return Some(EachItem {
+                    key: Some(first),
+                    value: second,
+                });
+            }
+        }
+        Some(EachItem {
+            key: None,
+            value: first,
+        })
+    }
+}
+
+/// One-at-a-time setter from `#[builder(each = "...")]`.
+pub(crate) struct Each {
+    pub(crate) name: syn::Ident,
+    pub(crate) item: EachItem,
+    pub(crate) merge: Merge,
+}
+
+/// The type that the all-at-once setter of the non-required `field` takes,
+/// its parameter, and the value that the setter stores.
+pub(crate) fn bulk_param(field: &BuilderField) -> (&syn::Type, TokenStream2, TokenStream2) {
+    let options = &field.setter;
+    // The storage of an `Option` field is that `Option` itself, which the
+    // setter either fills or replaces.
+    let setter_ty = match field.option_inner() {
+        Some(inner) if options.strip_option => inner,
+        _ => &field.ty,
+    };
+    let (arg, value) = options.param("a", setter_ty);
+    let stored = if is_option(&field.ty) && !options.strip_option {
+        value
+    } else {
+        quote! { Some(#value) }
+    };
+    (setter_ty, arg, stored)
+}
+
+/// The setter `try_{name}`, which converts its argument into the `ty` that
+/// the setter `name` takes and passes it on, or fails with the conversion's
+/// error. It takes the builder as `receiver` and returns it as `returns`.
+pub(crate) fn try_setter(
+    vis: &syn::Visibility,
+    name: &syn::Ident,
+    ty: &syn::Type,
+    receiver: TokenStream2,
+    returns: TokenStream2,
+) -> TokenStream2 {
+    let try_name = format_ident!("try_{}", name.unraw());
+    quote! {
+        #vis fn #try_name<__V: ::core::convert::TryInto<#ty>>(
+            #receiver,
+            value: __V,
+        ) -> ::core::result::Result<#returns, <__V as ::core::convert::TryInto<#ty>>::Error> {
+            ::core::result::Result::Ok(self.#name(::core::convert::TryInto::try_into(value)?))
+        }
+    }
+}
+
+/// The names of the setters that [`field_setters`] generates for `field`.
+pub(crate) fn setter_names(field: &BuilderField) -> Vec<syn::Ident> {
+    let field_id = &field.ident;
+    let options = &field.setter;
+    if options.custom || options.skip || field.start {
+        return Vec::new();
+    }
+    if field.sub_builder.is_some() {
+        let field_mut = format_ident!("{}_mut", field_id.unraw(), span = field_id.span());
+        return vec![field_id.clone(), field_mut];
+    }
+    let mut names = Vec::new();
+    if let Some(each) = &field.each {
+        names.push(each.name.clone());
+        if each.name == *field_id {
+            return names;
+        }
+    }
+    names.push(field_id.clone());
+    if options.try_setter {
+        names.push(format_ident!(
+            "try_{}",
+            field_id.unraw(),
+            span = field_id.span()
+        ));
+    }
+    names
+}
+
+/// The setters for a field that is stored as an `Option` in the builder,
+/// whose other setters are generated with `setter` for the given `pattern`.
+pub(crate) fn field_setters(
+    field: &BuilderField,
+    pattern: Pattern,
+    setter: &Setter,
+) -> Vec<TokenStream2> {
+    let field_id = &field.ident;
+    let options = &field.setter;
+    let vis = &options.vis;
+    let setter = |name: &syn::Ident, args, assign| {
+        let method = setter(name, args, assign);
+        quote! { #vis #method }
+    };
+    let mut setters = Vec::new();
+    // `start` fields are given to the function that creates the builder.
+    if options.custom || options.skip || field.start {
+        return setters;
+    }
+
+    // The builder of a `sub_builder` field is handed to a closure, or
+    // borrowed to be set directly.
+    if let Some(sub_builder) = &field.sub_builder {
+        setters.push(setter(
+            field_id,
+            quote! {
+                build: impl for<'__b> ::core::ops::FnOnce(&'__b mut #sub_builder) -> &'__b mut #sub_builder
+            },
+            quote! { build(&mut builder.#field_id); },
+        ));
+        let field_mut = format_ident!("{}_mut", field_id.unraw());
+        setters.push(quote! {
+            #vis fn #field_mut(&mut self) -> &mut #sub_builder {
+                &mut self.#field_id
+            }
+        });
+        return setters;
+    }
+
+    if let Some(each) = &field.each {
+        let (value_arg, value) = options.param("value", &each.item.value);
+        let (args, item) = match &each.item.key {
+            None => (value_arg, value),
+            Some(key) => {
+                let (key_arg, key) = options.param("key", key);
+                (quote! { #key_arg, #value_arg }, quote! { (#key, #value) })
+            }
+        };
+        setters.push(setter(
+            &each.name,
+            args,
+            quote! {
+                ::core::iter::Extend::extend(
+                    builder.#field_id.get_or_insert_with(::core::default::Default::default),
+                    ::core::iter::once(#item),
+                );
+            },
+        ));
+        // If the one-at-a-time setter is given the same name as the field,
+        // there is no all-at-once setter because the names would conflict.
+        if each.name == *field_id {
+            return setters;
+        }
+    }
+
+    let (setter_ty, arg, stored) = bulk_param(field);
+    setters.push(setter(
+        field_id,
+        arg,
+        quote! { builder.#field_id = #stored; },
+    ));
+    if options.try_setter {
+        let (receiver, returns) = pattern.receiver();
+        setters.push(try_setter(vis, field_id, setter_ty, receiver, returns));
+    }
+    setters
+}
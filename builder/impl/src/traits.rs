@@ -0,0 +1,119 @@
+//! Implementations of the traits of the `derive_builder` crate: `Buildable`
+//! for the input type and `Builder` for the generated builder.
+//!
+//! Both forward to the generated inherent functions, so that generic code
+//! behaves exactly like code that names the builder. Builders also implement
+//! a hidden trait that only names the error of `build`, for `sub_builder`
+//! fields.
+
+use crate::{BuilderField, BuilderTarget, Pattern};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+/// `Buildable` for the input type, whose builder is `builder_ty`. Enums and
+/// builders with `start` fields have no `builder()` without arguments to
+/// forward to.
+pub(crate) fn expand_buildable(
+    fields: &[BuilderField],
+    target: &BuilderTarget,
+    builder_ty: &TokenStream2,
+) -> Option<TokenStream2> {
+    if target.is_variant || fields.iter().any(|field| field.start) {
+        return None;
+    }
+    let BuilderTarget {
+        output,
+        generics,
+        builder_fn,
+        ..
+    } = target;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Some(quote! {
+        impl #impl_generics ::derive_builder::Buildable for #output #ty_generics #where_clause {
+            type Builder = #builder_ty;
+
+            fn builder() -> Self::Builder {
+                <#output #ty_generics>::#builder_fn()
+            }
+        }
+    })
+}
+
+/// `Builder` for what the `build` of `builder_ty` takes: `&mut` or `&` the
+/// builder unless it is owned. `builder_ty` is the builder in the state where
+/// it can be built, `generics` are the parameters of that state and `bounds`
+/// the extra bounds that `build` has.
+///
+/// Implementing the trait on the builder itself would have `builder.build()`
+/// pick the trait's method, which takes the builder by value, over a `build`
+/// that borrows it, wherever the trait is in scope along with the derive.
+///
+/// A trait implementation is as public as the builder, so there is none when
+/// `build` is `build_fn(private)` or has a visibility other than `pub`.
+pub(crate) fn expand_builder(
+    target: &BuilderTarget,
+    generics: &syn::Generics,
+    builder_ty: &TokenStream2,
+    bounds: &[TokenStream2],
+) -> Option<TokenStream2> {
+    let vis = target.options.method_vis();
+    if target.options.build_fn.private || !matches!(vis, syn::Visibility::Public(_)) {
+        return None;
+    }
+    let output = target.output;
+    let (_, ty_generics, _) = target.generics.split_for_impl();
+    let pattern = if target.options.typestate {
+        Pattern::Owned
+    } else {
+        target.options.pattern
+    };
+    let mut generics = generics.clone();
+    let receiver_ty = match pattern {
+        Pattern::Owned => quote! { #builder_ty },
+        Pattern::Mutable | Pattern::Immutable => {
+            generics.params.insert(0, syn::parse_quote!('__builder));
+            if pattern == Pattern::Mutable {
+                quote! { &'__builder mut #builder_ty }
+            } else {
+                quote! { &'__builder #builder_ty }
+            }
+        }
+    };
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let predicates = where_clause
+        .into_iter()
+        .flat_map(|where_clause| &where_clause.predicates);
+    let build_error = target.build_error();
+    let (_, build_fn) = target.options.build_fn.signature(vis);
+
+    Some(quote! {
+        impl #impl_generics ::derive_builder::Builder for #receiver_ty
+        where
+            #(#predicates,)*
+            #(#bounds)*
+        {
+            type Output = #output #ty_generics;
+            type Error = #build_error;
+
+            fn build(self) -> ::core::result::Result<Self::Output, Self::Error> {
+                <#builder_ty>::#build_fn(self)
+            }
+        }
+    })
+}
+
+/// The hidden `BuildFnError` for `builder_ty`, whose parameters are those of
+/// `target`. It names the error of `build` for builders that hold this one in
+/// a `sub_builder` field, also when there is no `Builder` implementation.
+pub(crate) fn expand_build_fn_error(
+    target: &BuilderTarget,
+    builder_ty: &TokenStream2,
+) -> TokenStream2 {
+    let (impl_generics, _, where_clause) = target.generics.split_for_impl();
+    let build_error = target.build_error();
+    quote! {
+        impl #impl_generics ::derive_builder::__private::BuildFnError for #builder_ty #where_clause {
+            type Error = #build_error;
+        }
+    }
+}
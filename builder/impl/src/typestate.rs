@@ -0,0 +1,402 @@
+//! Builders for `#[builder(typestate)]`.
+//!
+//! Every required field gets a type parameter on the builder which is `()`
+//! while the field is unset and `(T,)` once it holds a `T`. The setter of a
+//! required field only exists in the unset state and moves the builder into
+//! the set state, and `build` only exists once every required field is set.
+//! Forgetting a field and setting one twice are therefore both type errors.
+//! The fields of an `exactly_one` or `at_most_one` group share a type
+//! parameter the same way, which the first of them to be set moves into
+//! the set state.
+
+use crate::group::Rule;
+use crate::{
+    build_preamble, camel_case, error, expand_to_builder, phantom_field, setter, start_params,
+    traits, Builder, BuilderField, BuilderTarget, Pattern,
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+/// A type parameter of the builder that tracks a required field, or the
+/// fields of a group. Fields and groups name their parameters with different
+/// prefixes, so that a field `auth_group` and a group `auth` do not clash.
+struct State {
+    param: syn::Ident,
+    /// The builder's field of type `param`, which for a required field is
+    /// the field's own storage.
+    storage: syn::Ident,
+    /// The type of `storage` in the set state.
+    set: TokenStream2,
+    /// Whether `build` needs the state to be set, or is available in both.
+    required: bool,
+    /// Whether the state is a group's, stored apart from its fields.
+    is_group: bool,
+}
+
+/// The arguments that instantiate `generics` with its own parameters, e.g.
+/// `'a, T, N` for `<'a, T: Trait, const N: usize>`.
+fn generic_args(generics: &syn::Generics) -> Vec<TokenStream2> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote! { #lifetime }
+            }
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+            syn::GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+        })
+        .collect()
+}
+
+/// `generics` with the type parameters `params` appended, each defaulting to
+/// `default` if one is given.
+fn with_params<'a>(
+    generics: &syn::Generics,
+    params: impl IntoIterator<Item = &'a syn::Ident>,
+    default: Option<syn::Type>,
+) -> syn::Generics {
+    let mut generics = generics.clone();
+    for param in params {
+        let mut param = syn::TypeParam::from(param.clone());
+        if let Some(default) = &default {
+            param.eq_token = Some(Default::default());
+            param.default = Some(default.clone());
+        }
+        generics.params.push(syn::GenericParam::Type(param));
+    }
+    generics
+}
+
+pub(crate) fn expand_builder(fields: &[BuilderField], target: &BuilderTarget) -> Builder {
+    let BuilderTarget {
+        output,
+        generics,
+        path,
+        builder_name,
+        vis,
+        builder_fn,
+        ..
+    } = target;
+
+    // One state parameter per required field and per group, and for each
+    // field the index of its state, `None` for the others.
+    let mut states = Vec::new();
+    let mut group_states = vec![None; target.groups.len()];
+    let field_states: Vec<Option<usize>> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            if field.is_required() {
+                let ty = &field.ty;
+                states.push(State {
+                    param: format_ident!("__F{}", camel_case(&field.ident)),
+                    storage: field.ident.clone(),
+                    set: quote! { (#ty,) },
+                    required: true,
+                    is_group: false,
+                });
+                return Some(states.len() - 1);
+            }
+            let group_index = target
+                .groups
+                .iter()
+                .position(|group| group.members.contains(&index))?;
+            let group = &target.groups[group_index];
+            Some(*group_states[group_index].get_or_insert_with(|| {
+                states.push(State {
+                    param: format_ident!("__G{}", camel_case(&group.name)),
+                    storage: format_ident!("__{}_group", group.name.unraw()),
+                    set: quote! { ((),) },
+                    required: group.rule == Rule::ExactlyOne,
+                    is_group: true,
+                });
+                states.len() - 1
+            }))
+        })
+        .collect();
+    let state_params: Vec<&syn::Ident> = states.iter().map(|state| &state.param).collect();
+
+    let user_args = generic_args(generics);
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let decl_generics = with_params(
+        generics,
+        state_params.iter().copied(),
+        Some(syn::parse_quote!(())),
+    );
+    let any_state_generics = with_params(generics, state_params.iter().copied(), None);
+    let (any_state_impl_generics, _, _) = any_state_generics.split_for_impl();
+
+    // Everything that a setter moves over into the builder it returns.
+    let storage_idents: Vec<&syn::Ident> = fields
+        .iter()
+        .filter(|field| field.is_stored() && !field.is_required())
+        .map(|field| &field.ident)
+        .chain(states.iter().map(|state| &state.storage))
+        .collect();
+    let phantom = phantom_field(target);
+
+    let mut qbuilder_field_declarations = Vec::new();
+    let mut qbuilder_field_assignments = Vec::new();
+    let mut qbuilder_method_definitions = Vec::new();
+    let mut qbuilder_setter_impls = Vec::new();
+    let mut qbuilder_build_assignments = Vec::new();
+
+    for (field, state_index) in fields.iter().zip(&field_states) {
+        let field_id = &field.ident;
+        let member = &field.member;
+        let ty = &field.ty;
+
+        if !field.is_stored() {
+            let value = field.build_value(quote! {}, target);
+            qbuilder_build_assignments.push(quote! {
+                #member: #value,
+            });
+            continue;
+        }
+
+        let Some(state_index) = *state_index else {
+            // Fields that may be left unset are stored and set the same way
+            // as in a regular builder, except that setters take the builder
+            // by value.
+            let field_ty = field.storage_ty();
+            qbuilder_field_declarations.push(quote! {
+                #field_id: #field_ty,
+            });
+
+            qbuilder_method_definitions.extend(setter::field_setters(
+                field,
+                Pattern::Owned,
+                &setter::owned_setter,
+            ));
+            let initial = field.initial();
+            qbuilder_field_assignments.push(quote! {
+                #field_id: #initial,
+            });
+            // A sub-builder is moved out of the builder to be built.
+            let stored = if field.sub_builder.is_some() {
+                quote! { { self.#field_id } }
+            } else {
+                quote! { self.#field_id }
+            };
+            let value = field.build_value(stored, target);
+            qbuilder_build_assignments.push(quote! {
+                #member: #value,
+            });
+            continue;
+        };
+
+        let state = &states[state_index];
+        let (setter_ty, arg, set) = if field.is_required() {
+            let param = &state.param;
+            qbuilder_field_declarations.push(quote! {
+                #field_id: #param,
+            });
+            qbuilder_field_assignments.push(quote! {
+                #field_id: (),
+            });
+            qbuilder_build_assignments.push(quote! {
+                #member: self.#field_id.0,
+            });
+            let (arg, value) = field.setter.param("a", ty);
+            (ty, arg, quote! { #field_id: (#value,), })
+        } else {
+            // A field of a group is stored like any other that may be left
+            // unset, next to the group's state.
+            let field_ty = field.storage_ty();
+            qbuilder_field_declarations.push(quote! {
+                #field_id: #field_ty,
+            });
+            qbuilder_field_assignments.push(quote! {
+                #field_id: None,
+            });
+            let value = field.build_value(quote! { self.#field_id }, target);
+            qbuilder_build_assignments.push(quote! {
+                #member: #value,
+            });
+            let (setter_ty, arg, stored) = setter::bulk_param(field);
+            let group_storage = &state.storage;
+            (
+                setter_ty,
+                arg,
+                quote! { #field_id: #stored, #group_storage: ((),), },
+            )
+        };
+
+        // The setter is available whatever the other states are, but only
+        // while this one is unset. A hand-written one with `setter(custom)`
+        // has to make the same state transition.
+        if field.setter.custom {
+            continue;
+        }
+        let other_states = state_params
+            .iter()
+            .copied()
+            .filter(|param| **param != state.param);
+        let setter_generics = with_params(generics, other_states, None);
+        let (setter_impl_generics, _, _) = setter_generics.split_for_impl();
+        let state_args = |this: TokenStream2| {
+            states.iter().enumerate().map(move |(i, state)| {
+                if i == state_index {
+                    this.clone()
+                } else {
+                    let param = &state.param;
+                    quote! { #param }
+                }
+            })
+        };
+        let unset_args = state_args(quote! { () });
+        let set_args = state_args(state.set.clone());
+        let others = storage_idents
+            .iter()
+            .filter(|other| **other != field_id && **other != &state.storage)
+            .map(|other| quote! { #other: self.#other, });
+        let setter_vis = &field.setter.vis;
+        let phantom_move = phantom
+            .as_ref()
+            .map(|_| quote! { __phantom: self.__phantom, });
+        let set_builder = quote! { #builder_name <#(#user_args,)* #(#set_args),*> };
+        let try_setter = field.setter.try_setter.then(|| {
+            setter::try_setter(
+                setter_vis,
+                field_id,
+                setter_ty,
+                quote! { self },
+                set_builder.clone(),
+            )
+        });
+        qbuilder_setter_impls.push(quote! {
+            impl #setter_impl_generics #builder_name <#(#user_args,)* #(#unset_args),*> #where_clause {
+                #setter_vis fn #field_id(self, #arg) -> #set_builder {
+                    #builder_name {
+                        #set
+                        #(#others)*
+                        #phantom_move
+                    }
+                }
+
+                #try_setter
+            }
+        });
+    }
+
+    // Required fields are stored in their state, groups next to their
+    // fields.
+    let group_storages: Vec<&State> = states.iter().filter(|state| state.is_group).collect();
+    for State { param, storage, .. } in &group_storages {
+        qbuilder_field_declarations.push(quote! {
+            #storage: #param,
+        });
+        qbuilder_field_assignments.push(quote! {
+            #storage: (),
+        });
+    }
+    if let Some((declaration, assignment)) = phantom {
+        qbuilder_field_declarations.push(declaration);
+        qbuilder_field_assignments.push(assignment);
+    }
+
+    let unset_states = state_params.iter().map(|_| quote! { () });
+    let unset_builder = quote! { #builder_name <#(#user_args,)* #(#unset_states),*> };
+    let set_states = states.iter().map(|state| &state.set);
+    // A builder made from a value has every required field set already, and
+    // every group is taken to be set too.
+    let set_builder = quote! { #builder_name <#(#user_args,)* #(#set_states),*> };
+    let group_sets = group_storages.iter().map(|state| {
+        let storage = &state.storage;
+        quote! { #storage: ((),), }
+    });
+    let (to_builder, from_output) = expand_to_builder(
+        fields,
+        target,
+        &set_builder,
+        quote! { #(#group_sets)* },
+        |field, value| {
+            if field.is_required() {
+                quote! { (#value,) }
+            } else {
+                field.store(value)
+            }
+        },
+    );
+    let build_preamble = build_preamble(fields, target);
+    let (build_vis, build_fn) = target
+        .options
+        .build_fn
+        .signature(target.options.method_vis());
+    let build_error = target.build_error();
+    let error_items = error::expand_error(fields, target);
+    // `build` is available whatever the state of `at_most_one` groups.
+    let build_generics = with_params(
+        generics,
+        states
+            .iter()
+            .filter(|state| !state.required)
+            .map(|state| &state.param),
+        None,
+    );
+    let (build_impl_generics, _, _) = build_generics.split_for_impl();
+    let build_states = states.iter().map(|state| {
+        if state.required {
+            state.set.clone()
+        } else {
+            let param = &state.param;
+            quote! { #param }
+        }
+    });
+    let built_builder = quote! { #builder_name <#(#user_args,)* #(#build_states),*> };
+    let buildable = traits::expand_buildable(fields, target, &unset_builder);
+    let builder_impl = traits::expand_builder(target, &build_generics, &built_builder, &[]);
+
+    let start_params = start_params(fields);
+    let constructor = quote! {
+        #vis fn #builder_fn (#(#start_params),*) -> #unset_builder {
+            #builder_name {
+                #(#qbuilder_field_assignments)*
+            }
+        }
+
+        #to_builder
+    };
+
+    let builder_attrs = target.options.builder_attrs();
+    let items = quote! {
+        #builder_attrs
+        #vis struct #builder_name #decl_generics #where_clause {
+            #(#qbuilder_field_declarations)*
+        }
+
+        impl #any_state_impl_generics #builder_name <#(#user_args,)* #(#state_params),*> #where_clause {
+            #(#qbuilder_method_definitions)*
+        }
+
+        #(#qbuilder_setter_impls)*
+
+        impl #build_impl_generics #built_builder #where_clause {
+            #build_vis fn #build_fn(self) -> ::core::result::Result<#output #ty_generics, #build_error> {
+                #build_preamble
+                Ok(#path {
+                    #(#qbuilder_build_assignments)*
+                })
+            }
+        }
+
+        #error_items
+
+        #from_output
+
+        #buildable
+
+        #builder_impl
+    };
+
+    Builder { constructor, items }
+}
@@ -0,0 +1,131 @@
+//! Checks declared on fields with `#[builder(validate(...))]`.
+//!
+//! `range = a..=b` and `len = a..=b` check the value, or its `len()`, against
+//! a range, `non_empty` checks that a collection is not empty, and
+//! `with = "path"` calls `path(&value)`, which returns a `Result<(), E>` with
+//! `E: Display`. The checks apply to the values the fields were set to, and
+//! `build` runs all of them before failing with every violation at once.
+
+use crate::attr::{self, Errors};
+use crate::{BuilderField, BuilderTarget};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+
+/// One check from `validate(...)`.
+pub(crate) enum Validator {
+    Range(syn::Expr),
+    Len(syn::Expr),
+    NonEmpty,
+    With(syn::Path),
+}
+
+impl Validator {
+    /// Parses the checks in `validate(...)` into `validators`.
+    pub(crate) fn parse_all(
+        meta: &ParseNestedMeta,
+        validators: &mut Vec<Validator>,
+        errors: &mut Errors,
+    ) -> syn::Result<()> {
+        attr::parse_nested(meta, errors, |meta, _| {
+            validators.push(if meta.path.is_ident("range") {
+                Validator::Range(meta.value()?.parse()?)
+            } else if meta.path.is_ident("len") {
+                Validator::Len(meta.value()?.parse()?)
+            } else if meta.path.is_ident("non_empty") {
+                Validator::NonEmpty
+            } else if meta.path.is_ident("with") {
+                Validator::With(attr::parse_str(meta)?)
+            } else {
+                return Err(attr::unknown_option(
+                    meta,
+                    &["range", "len", "non_empty", "with"],
+                ));
+            });
+            Ok(())
+        })
+    }
+}
+
+/// The checks of the fields' validators, which `build` runs before building
+/// the value. Adds an error with every violation to `failures` if there are
+/// any.
+pub(crate) fn expand_validators(fields: &[BuilderField], target: &BuilderTarget) -> TokenStream2 {
+    if !uses_validators(fields) {
+        return quote! {};
+    }
+    let error_name = target.error_name();
+    let alloc = target.options.alloc_crate();
+
+    let checks = fields.iter().flat_map(|field| {
+        let name = field.ident.unraw().to_string();
+        let stored = field.set_value(target);
+        let alloc = alloc.clone();
+        field.validators.iter().map(move |validator| {
+            let (check, message) = match validator {
+                Validator::Range(range) => {
+                    let message = format!("{} must be in {}", name, text(range));
+                    (
+                        quote! { (#range).contains(value) },
+                        quote! { #alloc::string::ToString::to_string(#message) },
+                    )
+                }
+                Validator::Len(range) => {
+                    let message = format!("{} must have a length in {}", name, text(range));
+                    (
+                        quote! { (#range).contains(&value.len()) },
+                        quote! { #alloc::string::ToString::to_string(#message) },
+                    )
+                }
+                Validator::NonEmpty => {
+                    let message = format!("{} must not be empty", name);
+                    let message = quote! { #alloc::string::ToString::to_string(#message) };
+                    // A collection that nothing was added to is empty.
+                    if field.each.is_some() {
+                        return quote! {
+                            if #stored.map_or(true, |value| value.is_empty()) {
+                                violations.push(#message);
+                            }
+                        };
+                    }
+                    (quote! { !value.is_empty() }, message)
+                }
+                Validator::With(path) => {
+                    return quote! {
+                        if let ::core::option::Option::Some(value) = #stored {
+                            if let ::core::result::Result::Err(err) = #path(value) {
+                                violations.push(#alloc::format!("{}: {}", #name, err));
+                            }
+                        }
+                    };
+                }
+            };
+            quote! {
+                if let ::core::option::Option::Some(value) = #stored {
+                    if !#check {
+                        violations.push(#message);
+                    }
+                }
+            }
+        })
+    });
+
+    quote! {
+        let mut violations: #alloc::vec::Vec<#alloc::string::String> = #alloc::vec::Vec::new();
+        #(#checks)*
+        if !violations.is_empty() {
+            failures.push(#error_name::InvalidFields(violations));
+        }
+    }
+}
+
+/// The source text of `expr`, for messages.
+fn text(expr: &syn::Expr) -> String {
+    quote!(#expr).to_string().replace(' ', "")
+}
+
+/// Whether any of the fields declares a validator.
+pub(crate) fn uses_validators(fields: &[BuilderField]) -> bool {
+    fields.iter().any(|field| !field.validators.is_empty())
+}
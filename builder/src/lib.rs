@@ -1,170 +1,90 @@
-use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::spanned::Spanned;
+// Crates that have the "proc-macro" crate type are only allowed to export
+// procedural macros, so the traits that every generated builder implements
+// live in this crate, and the derive in derive_builder_impl is re-exported
+// from here. Users only need to depend on this one crate.
+//
+// The derive and the trait share the name `Builder`, which is fine because
+// macros and traits live in different namespaces.
 
-fn is_option(ty: &syn::Type) -> bool {
-    let syn::Type::Path(tp) = ty else {
-        return false;
-    };
+#![no_std]
 
-    tp.path
-        .segments
-        .first()
-        .is_some_and(|s| s.ident == "Option")
-}
-
-#[proc_macro_derive(Builder, attributes(builder))]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let input = syn::parse_macro_input!(input as syn::DeriveInput);
-
-    let mut qbuilder_field_declarations = Vec::new();
-    let mut qbuilder_method_definitions = Vec::new();
-    let mut qbuilder_field_assignments = Vec::new();
-    let mut qbuilder_build_assignments = Vec::new();
-
-    match input.data {
-        syn::Data::Struct(data_struct) => {
-            for field in data_struct.fields {
-                let field_ident = field.ident.clone();
+use core::fmt;
 
-                // Wrap non-option T to Option<T>, otherwise don't wrap it
-                let field_ty = field.ty.clone();
-                let field_ty = if is_option(&field_ty) {
-                    quote! { #field_ty }
-                } else {
-                    quote! { ::core::option::Option<#field_ty> }
-                };
-                qbuilder_field_declarations.push(quote! {
-                    pub #field_ident: #field_ty,
-                });
+pub use derive_builder_impl::Builder;
 
-                if let Some(ref field_id) = field.ident {
-                    let mut agg = None;
+/// A type with a builder, implemented by `#[derive(Builder)]` on structs.
+///
+/// Enums have a builder per variant and structs with `#[builder(start)]`
+/// fields need their values to create one, so neither implements this trait.
+pub trait Buildable {
+    type Builder;
 
-                    for attr in &field.attrs {
-                        if attr.path().is_ident("builder") {
-                            let e = attr.parse_args::<syn::ExprAssign>().unwrap();
-                            if let syn::Expr::Path(lp) = &*e.left {
-                                if lp.path.is_ident("each") {
-                                    if let syn::Expr::Lit(lr) = &*e.right {
-                                        if let syn::Lit::Str(s) = &lr.lit {
-                                            agg = Some((&field_id, s.clone()));
-                                            break;
-                                        }
-                                    }
-                                } else {
-                                    return syn::Error::new(
-                                        lp.span(),
-                                        "expected `builder(each = \"...\")`",
-                                    )
-                                    .to_compile_error()
-                                    .into();
-                                }
-                            }
-                        }
-                    }
+    /// Creates an empty builder, the same as the derived `T::builder()`.
+    fn builder() -> Self::Builder;
+}
 
-                    let ty = field.ty.clone();
-                    if let Some((field_id, method_name)) = agg {
-                        let method_name = format_ident!("{}", method_name.value());
-                        if method_name != **field_id {
-                            qbuilder_method_definitions.push(quote! {
-                                fn #method_name(&mut self, a: String) -> &mut Self {
-                                    if let Some(s) = &mut self.#field_id {
-                                        s.push(a);
-                                    } else {
-                                        self.#field_id = Some(vec![a]);
-                                    }
-                                    self
-                                }
-                            });
-                        } else {
-                            qbuilder_method_definitions.push(quote! {
-                                fn #field_id(&mut self, a: #ty) -> &mut Self {
-                                    self.#field_id = Some(a);
-                                    self
-                                }
-                            });
-                        }
+/// A builder generated by `#[derive(Builder)]`.
+///
+/// The trait is implemented for whatever the builder's `build` takes:
+/// `&mut FooBuilder` by default, `&FooBuilder` with `pattern = "immutable"`,
+/// and `FooBuilder` itself with `pattern = "owned"` or `typestate`. Typestate
+/// builders only implement it once every required field is set.
+///
+/// Builders whose `build` is `build_fn(private)` or not `pub` do not implement
+/// it, since the implementation would make `build` public.
+pub trait Builder {
+    /// The type that the builder builds.
+    type Output;
+    /// The error that building fails with.
+    type Error;
+
+    /// Builds the value, the same as the builder's own `build` method.
+    fn build(self) -> Result<Self::Output, Self::Error>;
+}
 
-                        qbuilder_field_assignments.push(quote! {
-                            #field_id: Some(vec![]),
-                        });
-                    } else {
-                        let mut field_ty = ty.clone();
-                        // For `Option`, we use the inner generic argument type for `field_ty`.
-                        if let syn::Type::Path(p) = ty {
-                            if let Some(path) = p.path.segments.first() {
-                                if path.ident == "Option" {
-                                    if let syn::PathArguments::AngleBracketed(args) =
-                                        &path.arguments
-                                    {
-                                        if let Some(syn::GenericArgument::Type(inner_t)) =
-                                            args.args.first()
-                                        {
-                                            field_ty = inner_t.clone();
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        qbuilder_method_definitions.push(quote! {
-                            fn #field_id(&mut self, a: #field_ty) -> &mut Self {
-                                self.#field_id = Some(a);
-                                self
-                            }
-                        });
+/// The error of a builder generated by `#[derive(Builder)]`.
+///
+/// The builder of a `sub_builder` field fails with an error of its own, which
+/// the error of the outer builder holds and shows with the path to the field,
+/// as in `tls.cert is not set`.
+pub trait BuildError: fmt::Display {
+    /// Writes the error the way `Display` does, for the builder of the field
+    /// at `path`: the fields it names are prefixed with the path, and messages
+    /// about the builder as a whole start with it.
+    fn fmt_at(&self, path: Option<&FieldPath<'_>>, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
 
-                        qbuilder_field_assignments.push(quote! {
-                            #field_id: None,
-                        });
-                    }
+/// The path to a field through `sub_builder` fields, such as `server.tls`.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldPath<'a> {
+    parent: Option<&'a FieldPath<'a>>,
+    field: &'a str,
+}
 
-                    if let syn::Type::Path(type_path) = field.ty {
-                        if let Some(fs) = type_path.path.segments.first() {
-                            let error_message = format!("{} is not set", field_id);
-                            if fs.ident.eq("Option") {
-                                qbuilder_build_assignments.push(quote! {
-                                    #field_id: self.#field_id.clone(),
-                                });
-                            } else {
-                                qbuilder_build_assignments.push(quote! {
-                                    #field_id: self.#field_id.clone().ok_or(format!(#error_message).to_string())?,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        syn::Data::Enum(_) => todo!(),
-        syn::Data::Union(_) => todo!(),
+impl<'a> FieldPath<'a> {
+    /// The field `field` of the builder at `parent`, or of the outermost
+    /// builder if there is no parent.
+    pub fn new(parent: Option<&'a FieldPath<'a>>, field: &'a str) -> Self {
+        FieldPath { parent, field }
     }
+}
 
-    let struct_name = input.ident;
-    let builder_name = format_ident!("{}Builder", struct_name);
-    quote! {
-        impl #struct_name {
-            pub fn builder () -> #builder_name {
-                #builder_name {
-                    #(#qbuilder_field_assignments)*
-                }
-            }
-        }
-
-        pub struct #builder_name {
-            #(#qbuilder_field_declarations)*
+impl fmt::Display for FieldPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(parent) = self.parent {
+            write!(f, "{}.", parent)?;
         }
+        f.write_str(self.field)
+    }
+}
 
-        impl #builder_name {
-            fn build(&mut self) -> ::core::result::Result<#struct_name, ::std::boxed::Box<dyn ::core::error::Error>> {
-                Ok(#struct_name {
-                    #(#qbuilder_build_assignments)*
-                })
-            }
-
-            #(#qbuilder_method_definitions)*
-        }
+// Used by the generated code only.
+#[doc(hidden)]
+pub mod __private {
+    /// The error that a generated builder's `build` fails with, which the
+    /// builders holding it in a `sub_builder` field name in their own error.
+    /// Unlike `Builder`, it is implemented whatever `build`'s visibility.
+    pub trait BuildFnError {
+        type Error;
     }
-    .into()
 }
@@ -0,0 +1,66 @@
+// Enums get one builder per struct-like variant. The builder for a variant is
+// created by a snake_case constructor on the enum, named after the variant, and
+// its build method produces the enum. Required and optional fields follow the
+// same rules as for structs. A run of capitals such as `HTTP` is kept as one
+// word in the constructor's name.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Source {
+    File {
+        path: String,
+        mode: Option<u32>,
+    },
+    HttpServer {
+        url: String,
+        #[builder(each = "header")]
+        headers: Vec<String>,
+    },
+    HTTPProxy {
+        url: String,
+    },
+}
+
+fn main() {
+    let file = Source::file_builder()
+        .path("/etc/hosts".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        file,
+        Source::File {
+            path: "/etc/hosts".to_owned(),
+            mode: None,
+        }
+    );
+
+    let http = Source::http_server_builder()
+        .url("https://example.com".to_owned())
+        .header("Accept: */*".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        http,
+        Source::HttpServer {
+            url: "https://example.com".to_owned(),
+            headers: vec!["Accept: */*".to_owned()],
+        }
+    );
+
+    let proxy = Source::http_proxy_builder()
+        .url("http://proxy:3128".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        proxy,
+        Source::HTTPProxy {
+            url: "http://proxy:3128".to_owned(),
+        }
+    );
+
+    let mut builder: SourceFileBuilder = Source::file_builder();
+    builder.mode(0o644);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "path is not set");
+}
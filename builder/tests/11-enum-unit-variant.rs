@@ -0,0 +1,12 @@
+// Unit variants have nothing to build, so they are rejected with an error
+// pointing at the offending variant.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub enum Source {
+    Stdin,
+    File { path: String },
+}
+
+fn main() {}
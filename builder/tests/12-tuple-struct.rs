@@ -0,0 +1,76 @@
+// Tuple structs and tuple variants get positional setters named `_0`, `_1`,
+// and so on. A field can be given a proper setter name with
+// #[builder(name = "...")]. Newtype structs additionally implement From for
+// their single field, which skips the builder altogether. They do not when the
+// field has #[builder(...)] options, which From would bypass, or with
+// #[builder(no_from)] on the struct, for newtypes that implement it themselves.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Pair(String, Option<u32>);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Endpoint(#[builder(name = "host")] String, #[builder(name = "port")] u16);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Meters(f64);
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(no_from)]
+pub struct Name(String);
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Name(name.trim().to_owned())
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Name::from(name.as_str())
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Percent(#[builder(validate(range = 0..=100))] u8);
+
+impl From<u8> for Percent {
+    fn from(value: u8) -> Self {
+        Percent(value.min(100))
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Source {
+    Fd(i32),
+    File { path: String },
+}
+
+fn main() {
+    let pair = Pair::builder()._0("key".to_owned())._1(7).build().unwrap();
+    assert_eq!(pair, Pair("key".to_owned(), Some(7)));
+
+    let pair = Pair::builder()._0("key".to_owned()).build().unwrap();
+    assert_eq!(pair, Pair("key".to_owned(), None));
+
+    let err = Pair::builder()._1(7).build().unwrap_err();
+    assert_eq!(err.to_string(), "_0 is not set");
+
+    let endpoint = Endpoint::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .build()
+        .unwrap();
+    assert_eq!(endpoint, Endpoint("localhost".to_owned(), 8080));
+
+    assert_eq!(Meters::builder()._0(1.5).build().unwrap(), Meters(1.5));
+    assert_eq!(Meters::from(1.5), Meters(1.5));
+
+    assert_eq!(Name::from(" ferris ".to_owned()), Name("ferris".to_owned()));
+    assert_eq!(Percent::from(250), Percent(100));
+    assert!(Percent::builder()._0(250).build().is_err());
+
+    let fd = Source::fd_builder()._0(3).build().unwrap();
+    assert_eq!(fd, Source::Fd(3));
+}
@@ -0,0 +1,57 @@
+// The builder carries over the generic parameters of the input: lifetimes for
+// borrowed fields, type parameters with their bounds and where-clauses, and
+// const generics. Enum variants that don't use every parameter still get a
+// well-formed builder.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+pub trait Backend {
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Memory;
+
+impl Backend for Memory {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+}
+
+#[derive(Builder)]
+pub struct Config<'a, T: Backend, const N: usize>
+where
+    T: Debug,
+{
+    name: &'a str,
+    backend: T,
+    slots: [u8; N],
+    fallback: Option<&'a str>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Value<'a, T> {
+    Borrowed { value: &'a str },
+    Owned { value: T },
+}
+
+fn main() {
+    let name = String::from("primary");
+    let config = Config::builder()
+        .name(&name)
+        .backend(Memory)
+        .slots([0; 4])
+        .build()
+        .unwrap();
+    assert_eq!(config.name, "primary");
+    assert_eq!(config.backend.name(), "memory");
+    assert_eq!(config.slots.len(), 4);
+    assert!(config.fallback.is_none());
+
+    let borrowed: Value<u32> = Value::borrowed_builder().value("x").build().unwrap();
+    assert_eq!(borrowed, Value::Borrowed { value: "x" });
+
+    let owned: Value<u32> = Value::owned_builder().value(5).build().unwrap();
+    assert_eq!(owned, Value::Owned { value: 5 });
+}
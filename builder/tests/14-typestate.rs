@@ -0,0 +1,43 @@
+// With #[builder(typestate)] the required fields are tracked in the builder's
+// type parameters. Setters take the builder by value and return it in its new
+// state, and build() only exists once every required field has been set.
+// Optional and repeated fields can be set any number of times, in any order.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    priority: u8,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Wrapper<'a, T>(&'a str, T);
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .priority(3)
+        .current_dir("..".to_owned())
+        .arg("--release".to_owned())
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.priority, 3);
+
+    let partial: CommandBuilder<(String,)> = Command::builder().executable("cargo".to_owned());
+    let command = partial.priority(0).build().unwrap();
+    assert!(command.args.is_empty());
+
+    let wrapper = Wrapper::builder()._1(5)._0("five").build().unwrap();
+    assert_eq!(wrapper, Wrapper("five", 5));
+}
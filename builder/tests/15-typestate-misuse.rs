@@ -0,0 +1,21 @@
+// In typestate mode, calling build() before every required field is set, or
+// setting a required field a second time, fails to compile.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+    priority: u8,
+}
+
+fn main() {
+    let _ = Command::builder().executable("cargo".to_owned()).build();
+
+    let _ = Command::builder()
+        .priority(1)
+        .executable("cargo".to_owned())
+        .priority(2);
+}
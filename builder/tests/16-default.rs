@@ -0,0 +1,76 @@
+// Fields marked #[builder(default)] fall back to Default::default() when they
+// are never set, and #[builder(default = "...")] evaluates the given
+// expression instead. On the struct itself, #[builder(default)] takes every
+// unset field from the struct's own Default impl.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(default = "String::from(\"/\")")]
+    current_dir: String,
+    #[builder(default = "Some(10)")]
+    timeout: Option<u32>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(default)]
+pub struct Limits {
+    files: u32,
+    #[builder(default = "1")]
+    threads: u32,
+    label: Option<String>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            files: 1024,
+            threads: 8,
+            label: Some("default".to_owned()),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+    #[builder(default = "3")]
+    retries: u8,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.current_dir, "/");
+    assert_eq!(command.timeout, Some(10));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .timeout(5)
+        .build()
+        .unwrap();
+    assert_eq!(command.current_dir, "..");
+    assert_eq!(command.timeout, Some(5));
+
+    let limits = Limits::builder().files(16).build().unwrap();
+    assert_eq!(
+        limits,
+        Limits {
+            files: 16,
+            threads: 1,
+            label: Some("default".to_owned()),
+        }
+    );
+
+    let job = Job::builder().name("deploy".to_owned()).build().unwrap();
+    assert_eq!(job.retries, 3);
+}
@@ -0,0 +1,13 @@
+// The expression given to #[builder(default = "...")] is type checked against
+// the field, and errors in it point back at the attribute.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default = "\"10\"")]
+    timeout: u32,
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+// build() returns a generated error enum named after the builder, so callers
+// can tell which field was missing. With #[builder(error = "...")] build()
+// returns a type of your own instead, converted through its From impl.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Incomplete(Vec<String>),
+    Invalid(String),
+}
+
+impl From<ServerBuilderError> for ConfigError {
+    fn from(err: ServerBuilderError) -> Self {
+        match err {
+            ServerBuilderError::MissingField(field) => {
+                ConfigError::Incomplete(vec![field.to_owned()])
+            }
+            ServerBuilderError::MissingFields(fields) => ConfigError::Incomplete(fields),
+            ServerBuilderError::ValidationError(message) => ConfigError::Invalid(message),
+            other @ ServerBuilderError::Multiple(_) => ConfigError::Invalid(other.to_string()),
+        }
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(error = "ConfigError")]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn run() -> Result<Command, Box<dyn Error>> {
+    Ok(Command::builder().build()?)
+}
+
+fn main() {
+    let err = Command::builder()
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFields(vec!["executable".to_owned()]));
+    assert_eq!(err.to_string(), "executable is not set");
+    assert!(run().is_err());
+
+    let err: ConfigError = Server::builder()
+        .host("localhost".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err, ConfigError::Incomplete(vec!["port".to_owned()]));
+}
@@ -0,0 +1,32 @@
+// With #[builder(no_std)] the generated code only relies on core and alloc, so
+// the builder can be used in a no_std crate that has an allocator.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as _;
+
+use alloc::string::{String, ToString};
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(no_std)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+    #[builder(default = "3")]
+    retries: u8,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert!(command.current_dir.is_none());
+    assert_eq!(command.retries, 3);
+
+    let err = Command::builder().build().unwrap_err();
+    assert_eq!(err.to_string(), "executable is not set");
+}
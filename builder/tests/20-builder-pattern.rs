@@ -0,0 +1,48 @@
+// #[builder(pattern = "owned")] makes setters and build() take the builder by
+// value, so fields no longer need to be Clone. #[builder(pattern = "immutable")]
+// makes setters take &self and return an updated copy, which lets a partially
+// configured builder be reused as a template. The default stays "mutable".
+
+use derive_builder::Builder;
+use std::fs::File;
+use std::sync::mpsc::{self, Receiver};
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Worker {
+    name: String,
+    jobs: Receiver<u32>,
+    log: Option<File>,
+    handler: Box<dyn Fn(u32) -> u32>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let (sender, receiver) = mpsc::channel();
+    let worker = Worker::builder()
+        .name("doubler".to_owned())
+        .jobs(receiver)
+        .handler(Box::new(|job| job * 2))
+        .build()
+        .unwrap();
+    sender.send(21).unwrap();
+    assert_eq!((worker.handler)(worker.jobs.recv().unwrap()), 42);
+    assert_eq!(worker.name, "doubler");
+    assert!(worker.log.is_none());
+
+    let cargo = Command::builder().executable("cargo".to_owned());
+    let build = cargo.arg("build".to_owned()).build().unwrap();
+    let test = cargo.arg("test".to_owned()).current_dir("..".to_owned()).build().unwrap();
+    assert_eq!(build.args, vec!["build"]);
+    assert_eq!(test.args, vec!["test"]);
+    assert_eq!(test.current_dir.as_deref(), Some(".."));
+    assert!(cargo.build().unwrap().args.is_empty());
+}
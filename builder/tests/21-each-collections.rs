@@ -0,0 +1,55 @@
+// The one-at-a-time setter from #[builder(each = "...")] takes the element
+// type of the field's collection, and works with any collection that is
+// Default + Extend. Maps get a setter taking a key and a value. The
+// all-at-once setter is still generated next to it when the names differ.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+
+#[derive(Builder)]
+pub struct Build {
+    #[builder(each = "source")]
+    sources: Vec<PathBuf>,
+    #[builder(each = "feature")]
+    features: HashSet<String>,
+    #[builder(each = "target")]
+    targets: BTreeSet<&'static str>,
+    #[builder(each = "step")]
+    steps: VecDeque<u32>,
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+    #[builder(each = "define")]
+    defines: BTreeMap<String, Option<i64>>,
+}
+
+fn main() {
+    let build = Build::builder()
+        .source(PathBuf::from("src/lib.rs"))
+        .source(PathBuf::from("src/main.rs"))
+        .feature("std".to_owned())
+        .feature("std".to_owned())
+        .target("x86_64")
+        .target("aarch64")
+        .steps(VecDeque::from(vec![1, 2]))
+        .step(3)
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
+        .define("DEBUG".to_owned(), None)
+        .define("LEVEL".to_owned(), Some(3))
+        .build()
+        .unwrap();
+
+    assert_eq!(build.sources, vec![PathBuf::from("src/lib.rs"), PathBuf::from("src/main.rs")]);
+    assert_eq!(build.features.len(), 1);
+    assert_eq!(build.targets.into_iter().collect::<Vec<_>>(), vec!["aarch64", "x86_64"]);
+    assert_eq!(build.steps, VecDeque::from(vec![1, 2, 3]));
+    assert_eq!(build.env["RUST_LOG"], "debug");
+    assert_eq!(build.defines["LEVEL"], Some(3));
+
+    let build = Build::builder()
+        .sources(vec![PathBuf::from("build.rs")])
+        .build()
+        .unwrap();
+    assert_eq!(build.sources.len(), 1);
+    assert!(build.env.is_empty());
+}
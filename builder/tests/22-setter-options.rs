@@ -0,0 +1,60 @@
+// #[builder(setter(into))] makes setters take `impl Into<T>`, on a single
+// field or for all fields when given on the struct. setter(strip_option) is
+// the default behavior of taking `T` for an `Option<T>` field, and
+// setter(strip_option = false) makes the setter take the `Option<T>` itself.
+// setter(custom) leaves the setter to be written by hand.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(setter(strip_option))]
+    current_dir: Option<PathBuf>,
+    #[builder(setter(into = false, strip_option = false))]
+    timeout: Option<u32>,
+    #[builder(setter(custom))]
+    priority: u8,
+}
+
+impl CommandBuilder {
+    fn priority(&mut self, priority: u8) -> &mut Self {
+        self.priority = Some(priority.min(10));
+        self
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    #[builder(setter(into))]
+    name: String,
+    #[builder(setter(into), each = "tag")]
+    tags: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("/tmp")
+        .timeout(None)
+        .priority(50)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("/tmp")));
+    assert_eq!(command.timeout, None);
+    assert_eq!(command.priority, 10);
+
+    let job = Job::builder().tag("nightly").name("deploy").build().unwrap();
+    assert_eq!(job.name, "deploy");
+    assert_eq!(job.tags, vec!["nightly"]);
+}
@@ -0,0 +1,89 @@
+// #[builder(build_fn(validate = "..."))] runs a function on the builder
+// before build() constructs the value. Its error converts into a String and
+// comes back as the ValidationError variant of the build error.
+//
+// #[builder(build_fn(name = "...", private))] renames the generated build
+// method and keeps it private, so that a hand-written build() can wrap it.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct Server {
+    host: String,
+    port: u16,
+    cert: Option<String>,
+    insecure: Option<bool>,
+}
+
+impl ServerBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if self.port == Some(0) {
+            return Err("port must not be 0".to_owned());
+        }
+        if self.cert.is_none() && self.insecure != Some(true) {
+            return Err("either cert or insecure must be set".to_owned());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned", build_fn(name = "build_raw", private, validate = "check_name"))]
+pub struct User {
+    name: String,
+    #[builder(default)]
+    display_name: String,
+}
+
+fn check_name(builder: &UserBuilder) -> Result<(), &'static str> {
+    match &builder.name {
+        Some(name) if name.is_empty() => Err("name must not be empty"),
+        _ => Ok(()),
+    }
+}
+
+impl UserBuilder {
+    pub fn build(self) -> Result<User, UserBuilderError> {
+        let mut user = self.build_raw()?;
+        if user.display_name.is_empty() {
+            user.display_name = user.name.to_uppercase();
+        }
+        Ok(user)
+    }
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(0)
+        .insecure(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(server.to_string(), "port must not be 0");
+
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8443)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        server,
+        ServerBuilderError::ValidationError("either cert or insecure must be set".to_owned()),
+    );
+
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8443)
+        .cert("server.pem".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 8443);
+    assert_eq!(server.cert.as_deref(), Some("server.pem"));
+
+    let user = User::builder().name("ada".to_owned()).build().unwrap();
+    assert_eq!(user.display_name, "ADA");
+
+    let err = User::builder().name(String::new()).build().unwrap_err();
+    assert_eq!(err.to_string(), "name must not be empty");
+}
@@ -0,0 +1,58 @@
+// The builder and its error type take over the visibility of the input type,
+// while the builder's storage fields are always private. Setters and build()
+// are pub unless #[builder(vis = "...")] is given on the struct, or on a field
+// for that field's setters alone.
+//
+// That way a type with private fields can be constructed from other modules
+// through its builder only.
+
+mod net {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    #[builder(vis = "pub(crate)")]
+    pub struct Endpoint {
+        host: String,
+        port: u16,
+        #[builder(vis = "", default)]
+        retries: u32,
+    }
+
+    impl Endpoint {
+        pub fn host(&self) -> &str {
+            &self.host
+        }
+
+        pub fn port(&self) -> u16 {
+            self.port
+        }
+
+        pub fn retries(&self) -> u32 {
+            self.retries
+        }
+    }
+
+    impl EndpointBuilder {
+        // The private setter is only reachable through methods written in
+        // this module.
+        pub(crate) fn reliable(&mut self) -> &mut Self {
+            self.retries(3)
+        }
+    }
+}
+
+fn main() {
+    let endpoint = net::Endpoint::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .reliable()
+        .build()
+        .unwrap();
+
+    assert_eq!(endpoint.host(), "localhost");
+    assert_eq!(endpoint.port(), 8080);
+    assert_eq!(endpoint.retries(), 3);
+
+    let err: net::EndpointBuilderError = net::Endpoint::builder().build().unwrap_err();
+    assert_eq!(err.to_string(), "missing fields: host, port");
+}
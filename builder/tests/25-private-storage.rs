@@ -0,0 +1,16 @@
+// The builder's storage fields are private to the module that defines the
+// input type, so code elsewhere can only go through the setters.
+
+mod net {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Endpoint {
+        pub host: String,
+    }
+}
+
+fn main() {
+    let mut builder = net::Endpoint::builder();
+    builder.host = Some("localhost".to_owned());
+}
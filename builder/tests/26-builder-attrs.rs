@@ -0,0 +1,43 @@
+// #[builder(name = "...")] names the builder instead of appending "Builder"
+// to the struct's name, and its error type follows the new name.
+//
+// #[builder(derive(...))] derives traits for the builder, for example Clone to
+// reuse a partly filled builder as a template, and #[builder(struct_attr(...))]
+// passes any other attribute through to the builder struct.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(
+    name = "CmdSpec",
+    derive(Clone, Debug, PartialEq),
+    struct_attr(doc = "Specification of a command to run."),
+    struct_attr(must_use)
+)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut git = Command::builder();
+    git.executable("git".to_owned());
+
+    let mut status = git.clone();
+    status.arg("status".to_owned());
+    assert_ne!(status, git);
+
+    let mut log = git.clone();
+    log.arg("log".to_owned()).arg("--oneline".to_owned());
+    assert!(format!("{:?}", log).starts_with("CmdSpec"));
+
+    let status = status.build().unwrap();
+    assert_eq!(status.args, ["status"]);
+    let log = log.build().unwrap();
+    assert_eq!(log.args, ["log", "--oneline"]);
+
+    let err: CmdSpecError = CmdSpec::clone(&Command::builder()).build().unwrap_err();
+    assert_eq!(err, CmdSpecError::MissingFields(vec!["executable".to_owned()]));
+}
@@ -0,0 +1,46 @@
+// #[builder(setter(skip))] leaves a field off the builder entirely: there is
+// no setter and no storage for it, and build() fills it with
+// Default::default() or with the expression from #[builder(default = "...")].
+
+use derive_builder::Builder;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+#[derive(Builder)]
+pub struct Resolver<T> {
+    upstream: String,
+    #[builder(setter(skip))]
+    cache: RefCell<HashMap<String, T>>,
+    #[builder(setter(skip), default = "upstream_port()")]
+    port: u16,
+    #[builder(setter(skip))]
+    marker: PhantomData<T>,
+}
+
+fn upstream_port() -> u16 {
+    53
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Lookup {
+    name: String,
+    #[builder(setter(skip))]
+    attempts: u32,
+}
+
+fn main() {
+    let resolver = Resolver::<String>::builder()
+        .upstream("1.1.1.1".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(resolver.upstream, "1.1.1.1");
+    assert!(resolver.cache.borrow().is_empty());
+    assert_eq!(resolver.port, 53);
+    let PhantomData = resolver.marker;
+
+    let lookup = Lookup::builder().name("example.com".to_owned()).build().unwrap();
+    assert_eq!(lookup.name, "example.com");
+    assert_eq!(lookup.attempts, 0);
+}
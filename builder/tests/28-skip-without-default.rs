@@ -0,0 +1,14 @@
+// A skipped field is always built from its default, so its type has to
+// implement Default unless #[builder(default = "...")] gives an expression.
+
+use derive_builder::Builder;
+use std::fs::File;
+
+#[derive(Builder)]
+pub struct Logger {
+    name: String,
+    #[builder(setter(skip))]
+    file: File,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+// Every malformed #[builder] option is reported, all in the same compile and
+// each at the tokens that are wrong, instead of only the first one. Options
+// that look like a misspelling of a known option come with a suggestion.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned", setter(int))]
+pub struct Command {
+    #[builder]
+    executable: String,
+    #[builder(each)]
+    args: Vec<String>,
+    #[builder(each = 3)]
+    env: Vec<String>,
+    #[builder(defualt, each = "file", vis = "public")]
+    files: Vec<String>,
+    #[builder(colour = "red")]
+    current_dir: Option<String>,
+}
+
+fn main() {}
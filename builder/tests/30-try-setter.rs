@@ -0,0 +1,58 @@
+// #[builder(try_setter)] adds a fallible setter next to the regular one. For
+// a field `port: u16`, `try_port` takes any `impl TryInto<u16>` and returns
+// the builder in a Result whose error is the conversion's error, so calls
+// can be chained with `?`. On the struct it applies to every field.
+
+use derive_builder::Builder;
+use std::error::Error;
+use std::num::{NonZeroU32, TryFromIntError};
+
+#[derive(Builder, Debug)]
+pub struct Listener {
+    host: String,
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    backlog: Option<NonZeroU32>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, try_setter)]
+pub struct Pool {
+    size: u8,
+    idle: Option<u8>,
+}
+
+fn open(port: u64, backlog: u32) -> Result<Listener, Box<dyn Error>> {
+    let listener = Listener::builder()
+        .host("0.0.0.0".to_owned())
+        .try_port(port)?
+        .try_backlog(backlog)?
+        .build()?;
+    Ok(listener)
+}
+
+fn main() {
+    let listener = open(8080, 128).unwrap();
+    assert_eq!(listener.port, 8080);
+    assert_eq!(listener.backlog, NonZeroU32::new(128));
+
+    assert!(open(70000, 128).is_err());
+    assert!(open(8080, 0).is_err());
+
+    let mut builder = Listener::builder();
+    let err: TryFromIntError = builder.try_port(-1i32).err().unwrap();
+    assert_eq!(err, u16::try_from(-1i32).unwrap_err());
+
+    let pool = Pool::builder()
+        .try_size(16u64)
+        .unwrap()
+        .try_idle(4i64)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(pool.size, 16);
+    assert_eq!(pool.idle, Some(4));
+
+    assert!(Pool::builder().try_size(300).is_err());
+}
@@ -0,0 +1,75 @@
+// A value can be turned back into a builder that has every field filled in,
+// to change a few fields of an existing value and build a new one. That is
+// `From<T> for TBuilder`, and `value.to_builder()` when T is Clone. Fields
+// with #[builder(each = "...")] keep their elements, so that more can be
+// added.
+
+use derive_builder::Builder;
+use std::fs::File;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Config {
+    host: String,
+    timeout: u32,
+    proxy: Option<String>,
+    #[builder(each = "header")]
+    headers: Vec<String>,
+    #[builder(setter(skip))]
+    retries: u32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Output {
+    path: String,
+    file: File,
+}
+
+#[derive(Builder, Clone, Debug)]
+#[builder(typestate)]
+pub struct Point<T> {
+    x: T,
+    y: T,
+    #[builder(default)]
+    label: String,
+}
+
+fn main() {
+    let old = Config::builder()
+        .host("example.com".to_owned())
+        .timeout(30)
+        .header("Accept: */*".to_owned())
+        .build()
+        .unwrap();
+
+    let new = old
+        .to_builder()
+        .timeout(5)
+        .header("Connection: close".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(new.host, "example.com");
+    assert_eq!(new.timeout, 5);
+    assert_eq!(new.proxy, None);
+    assert_eq!(new.headers, ["Accept: */*", "Connection: close"]);
+
+    let same = ConfigBuilder::from(old.clone()).build().unwrap();
+    assert_eq!(same, old);
+
+    // Without Clone the value can still be moved into a builder.
+    let output = Output::builder()
+        .path("/dev/null".to_owned())
+        .file(File::open("/dev/null").unwrap())
+        .build()
+        .unwrap();
+    let output = OutputBuilder::from(output)
+        .path("null".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(output.path, "null");
+
+    let origin = Point::builder().x(0).y(0).build().unwrap();
+    let labeled = origin.to_builder().label("origin".to_owned()).build().unwrap();
+    assert_eq!((labeled.x, labeled.y), (0, 0));
+    assert_eq!(labeled.label, "origin");
+}
@@ -0,0 +1,47 @@
+// Layered configuration: builders with #[builder(merge_fn)] can be merged with
+// `merge(&mut self, other: Self)`, so that builders filled from different
+// sources are combined. Fields set in `other` override the ones in `self`, and
+// fields left unset in `other` are kept.
+//
+// Collections from #[builder(each = "...")] are appended to by default, or
+// replaced as a whole with #[builder(merge = "replace")].
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug)]
+#[builder(merge_fn)]
+pub struct Config {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    #[builder(each = "include")]
+    includes: Vec<String>,
+    #[builder(each = "env", merge = "replace")]
+    env: HashMap<String, String>,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults
+        .host("localhost".to_owned())
+        .port(80)
+        .include("defaults.conf".to_owned())
+        .env("LANG".to_owned(), "C".to_owned());
+
+    let mut file = Config::builder();
+    file.port(8080)
+        .include("site.conf".to_owned())
+        .env("TZ".to_owned(), "UTC".to_owned());
+
+    let mut cli = Config::builder();
+    cli.user("admin".to_owned());
+
+    let config = defaults.merge(file).merge(cli).build().unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.user.as_deref(), Some("admin"));
+    assert_eq!(config.includes, ["defaults.conf", "site.conf"]);
+    assert_eq!(config.env.len(), 1);
+    assert_eq!(config.env["TZ"], "UTC");
+}
@@ -0,0 +1,15 @@
+// #[builder(merge = "...")] only chooses how collections are merged. Any
+// other field set in the merged builder simply replaces the previous value.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(merge_fn)]
+pub struct Config {
+    #[builder(merge = "append")]
+    name: String,
+    #[builder(each = "path", merge = "extend")]
+    paths: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,71 @@
+// Fields can be read from environment variables, named either one by one
+// with #[builder(env = "...")] or for every field at once with a prefix from
+// #[builder(env_prefix = "...")]. `fill_from_env()` reads the variables that
+// are set into an existing builder, and `from_env()` starts a new builder
+// from them. Values are parsed with FromStr, and a value that fails to parse
+// is reported with the name of its variable.
+
+use derive_builder::Builder;
+use std::env;
+use std::net::IpAddr;
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(env = "TEST_SERVER_ADDR")]
+    addr: IpAddr,
+    #[builder(env = "TEST_SERVER_PORT")]
+    port: u16,
+    #[builder(env = "TEST_SERVER_NAME")]
+    name: Option<String>,
+    #[builder(default)]
+    workers: usize,
+}
+
+#[derive(Builder, Debug)]
+#[builder(env_prefix = "TEST_DB_")]
+pub struct Database {
+    url: String,
+    #[builder(default = "5")]
+    pool_size: u32,
+    #[builder(each = "replica")]
+    replicas: Vec<String>,
+}
+
+fn main() {
+    env::set_var("TEST_SERVER_ADDR", "127.0.0.1");
+    env::set_var("TEST_SERVER_PORT", "8080");
+    env::remove_var("TEST_SERVER_NAME");
+
+    let server = ServerBuilder::from_env().unwrap().workers(4).build().unwrap();
+    assert_eq!(server.addr, IpAddr::from([127, 0, 0, 1]));
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.name, None);
+    assert_eq!(server.workers, 4);
+
+    // Variables override what was set before, and unset ones leave fields
+    // as they were.
+    env::set_var("TEST_SERVER_NAME", "api");
+    let mut builder = Server::builder();
+    builder.port(1).name("web".to_owned());
+    let server = builder.fill_from_env().unwrap().build().unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.name.as_deref(), Some("api"));
+
+    env::set_var("TEST_SERVER_PORT", "eighty");
+    let err = ServerBuilder::from_env().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for TEST_SERVER_PORT: invalid digit found in string",
+    );
+
+    env::set_var("TEST_DB_URL", "postgres://localhost/app");
+    env::set_var("TEST_DB_POOL_SIZE", "20");
+    let database = DatabaseBuilder::from_env()
+        .unwrap()
+        .replica("postgres://replica/app".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(database.url, "postgres://localhost/app");
+    assert_eq!(database.pool_size, 20);
+    assert_eq!(database.replicas, ["postgres://replica/app"]);
+}
@@ -0,0 +1,111 @@
+// #[builder(cli)] generates `parse_args`, which fills a new builder from
+// command-line arguments. Each field is set by a --flag named after it in
+// kebab case, with the value following either as the next argument or after
+// `=`, and parsed with FromStr. `bool` fields are switches which are off
+// unless given. Fields with #[builder(each = "...")] have a flag named after
+// their one-at-a-time setter, which takes one element each time it is given.
+//
+// --help or -h fails with the help text, which lists the flags along with the
+// first line of each field's doc comment and marks the required ones.
+//
+// The arguments do not include the program name, so `std::env::args()` has to
+// be given as `std::env::args().skip(1)`.
+
+use derive_builder::Builder;
+use std::collections::BTreeMap;
+
+#[derive(Builder, Debug)]
+#[builder(cli)]
+pub struct Serve {
+    /// Directory to serve files from.
+    root: String,
+    /// Port to listen on.
+    listen_port: u16,
+    /// Log every request.
+    verbose: bool,
+    /// Name to announce.
+    name: Option<String>,
+    /// Extra response header.
+    #[builder(each = "header")]
+    headers: BTreeMap<String, String>,
+    #[builder(each = "index")]
+    index_files: Vec<String>,
+    #[builder(default = "4")]
+    threads: usize,
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn main() {
+    let serve = ServeBuilder::parse_args(args(&[
+        "--root",
+        "/srv/www",
+        "--listen-port=8080",
+        "--verbose",
+        "--header",
+        "Server=test",
+        "--header=Cache-Control=no-cache",
+        "--index",
+        "index.html",
+        "--index",
+        "index.htm",
+    ]))
+    .unwrap()
+    .build()
+    .unwrap();
+
+    assert_eq!(serve.root, "/srv/www");
+    assert_eq!(serve.listen_port, 8080);
+    assert!(serve.verbose);
+    assert_eq!(serve.name, None);
+    assert_eq!(serve.headers["Server"], "test");
+    assert_eq!(serve.headers["Cache-Control"], "no-cache");
+    assert_eq!(serve.index_files, ["index.html", "index.htm"]);
+    assert_eq!(serve.threads, 4);
+
+    let serve = ServeBuilder::parse_args(args(&["--root", ".", "--listen-port", "80"]))
+        .unwrap()
+        .build()
+        .unwrap();
+    assert!(!serve.verbose);
+
+    let err = ServeBuilder::parse_args(args(&["--listen-port", "http"])).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for `--listen-port`: invalid digit found in string",
+    );
+
+    let err = ServeBuilder::parse_args(args(&["--root"])).err().unwrap();
+    assert_eq!(err, ServeBuilderError::InvalidArgs("missing value for `--root`".to_owned()));
+
+    let err = ServeBuilder::parse_args(args(&["--port", "80"])).err().unwrap();
+    assert_eq!(err.to_string(), "unknown argument `--port`");
+
+    let err = ServeBuilder::parse_args(args(&["serve", "--root", "."])).err().unwrap();
+    assert_eq!(err.to_string(), "unknown argument `serve`");
+
+    let short = ServeBuilder::parse_args(args(&["-h"])).err().unwrap();
+    let err = ServeBuilder::parse_args(args(&["--help"])).err().unwrap();
+    assert_eq!(short, err);
+    let ServeBuilderError::HelpRequested(help) = err else {
+        panic!("expected the help text, got {:?}", err);
+    };
+    assert_eq!(
+        help,
+        "\
+Usage: Serve [OPTIONS]
+
+Options:
+  --root <ROOT>                Directory to serve files from. (required)
+  --listen-port <LISTEN_PORT>  Port to listen on. (required)
+  --verbose                    Log every request.
+  --name <NAME>                Name to announce.
+  --header <KEY=VALUE>...      Extra response header.
+  --index <INDEX>...
+  --threads <THREADS>
+  -h, --help                   Print this help
+",
+    );
+}
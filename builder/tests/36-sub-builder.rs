@@ -0,0 +1,100 @@
+// A field whose type derives Builder itself can be built in place with
+// #[builder(sub_builder)]. The outer builder holds the field's builder, which
+// is set either through a closure, `.tls(|b| b.cert(..))`, or by borrowing it
+// with `.tls_mut()`. `build()` builds the field's builder too. Its error is
+// kept in the `SubBuilder` variant, in a variant named after the field, and
+// shows the fields it is about by their path, such as `tls.cert`.
+
+use derive_builder::Builder;
+
+// Builders whose methods are not public can be held all the same.
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(vis = "pub(crate)")]
+pub struct Cert {
+    pem: String,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Tls {
+    cert: String,
+    #[builder(validate(len = 1..=64))]
+    key: String,
+    #[builder(default)]
+    verify: bool,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Server {
+    port: u16,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Chain {
+    #[builder(sub_builder = "CertBuilder")]
+    root: Cert,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.port(443).tls(|b| b.cert("cert.pem".to_owned()));
+    builder.tls_mut().key("key.pem".to_owned());
+    let server = builder.build().unwrap();
+    assert_eq!(server.port, 443);
+    assert_eq!(server.tls.cert, "cert.pem");
+    assert_eq!(server.tls.key, "key.pem");
+    assert!(!server.tls.verify);
+
+    // Setting the field again updates the same builder.
+    let mut builder = server.to_builder();
+    builder.tls(|b| b.verify(true));
+    let server = builder.build().unwrap();
+    assert_eq!(server.tls.key, "key.pem");
+    assert!(server.tls.verify);
+
+    let err = Server::builder()
+        .port(443)
+        .tls(|b| b.key("key.pem".to_owned()))
+        .build()
+        .unwrap_err();
+    assert_eq!(err, ServerBuilderError::MissingFields(vec!["tls.cert".to_owned()]));
+    assert_eq!(err.to_string(), "tls.cert is not set");
+
+    let err = Server::builder()
+        .port(443)
+        .tls(|b| b.cert("cert.pem".to_owned()).key(String::new()))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ServerBuilderError::SubBuilder(ServerBuilderFieldError::Tls(Box::new(
+            TlsBuilderError::InvalidFields(vec!["key must have a length in 1..=64".to_owned()]),
+        ))),
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid fields: tls.key must have a length in 1..=64",
+    );
+
+    let err = Chain::builder()
+        .root(|b| b.pem("root".to_owned()))
+        .server(|b| b.port(443).tls(|b| b.cert("cert.pem".to_owned())))
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "server.tls.key is not set");
+
+    let chain = Chain::builder()
+        .root(|b| b.pem("root".to_owned()))
+        .server(|b| {
+            b.port(443)
+                .tls(|b| b.cert("cert.pem".to_owned()).key("key.pem".to_owned()))
+        })
+        .build()
+        .unwrap();
+    assert_eq!(chain.root.pem, "root");
+    assert_eq!(chain.server, server.to_builder().tls(|b| b.verify(false)).build().unwrap());
+}
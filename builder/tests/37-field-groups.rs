@@ -0,0 +1,121 @@
+// Fields can be tied together by rules. The fields with the same
+// #[builder(group = "...")] form a group, of which `exactly_one` or
+// `at_most_one` field may be set, and a field with
+// #[builder(required_if = "other")] has to be set whenever `other` is.
+// `build()` checks the rules and reports a broken one through its error.
+//
+// In typestate mode the fields of a group share a state parameter, so that
+// the rules of groups are checked by the compiler instead.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Connection {
+    host: String,
+    #[builder(group = "auth", exactly_one)]
+    password: Option<String>,
+    #[builder(group = "auth")]
+    token: Option<String>,
+    #[builder(group = "auth", default)]
+    anonymous: bool,
+    #[builder(group = "proxy", at_most_one)]
+    proxy_host: Option<String>,
+    #[builder(group = "proxy")]
+    socks_host: Option<String>,
+    #[builder(required_if = "proxy_host")]
+    proxy_port: Option<u16>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Login {
+    user: String,
+    #[builder(group = "auth", exactly_one)]
+    password: Option<String>,
+    #[builder(group = "auth")]
+    token: Option<String>,
+    #[builder(group = "mfa", at_most_one)]
+    otp: Option<u32>,
+    #[builder(group = "mfa", default)]
+    sms: bool,
+    #[builder(required_if = "token")]
+    token_issuer: Option<String>,
+}
+
+fn main() {
+    let conn = Connection::builder()
+        .host("db".to_owned())
+        .token("secret".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(conn.token.as_deref(), Some("secret"));
+    assert!(conn.password.is_none());
+    assert!(!conn.anonymous);
+
+    let err = Connection::builder()
+        .host("db".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "one field of group auth must be set: password, token, anonymous"
+    );
+
+    let err = Connection::builder()
+        .host("db".to_owned())
+        .password("hunter2".to_owned())
+        .anonymous(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ConnectionBuilderError::ConflictingFields {
+            group: "auth",
+            fields: vec!["password", "anonymous"],
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "only one field of group auth can be set, got password, anonymous"
+    );
+
+    let err = Connection::builder()
+        .host("db".to_owned())
+        .anonymous(true)
+        .proxy_host("proxy".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "proxy_port is required when proxy_host is set");
+
+    let conn = Connection::builder()
+        .host("db".to_owned())
+        .anonymous(true)
+        .proxy_host("proxy".to_owned())
+        .proxy_port(1080)
+        .build()
+        .unwrap();
+    assert_eq!(conn.proxy_port, Some(1080));
+
+    let login = Login::builder()
+        .password("hunter2".to_owned())
+        .user("ferris".to_owned())
+        .build()
+        .unwrap();
+    assert!(login.otp.is_none() && !login.sms);
+
+    let login = Login::builder()
+        .user("ferris".to_owned())
+        .sms(true)
+        .token("secret".to_owned())
+        .token_issuer("idp".to_owned())
+        .build()
+        .unwrap();
+    assert!(login.sms);
+
+    let err = Login::builder()
+        .user("ferris".to_owned())
+        .token("secret".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "token_issuer is required when token is set");
+}
@@ -0,0 +1,22 @@
+// In typestate mode, setting a second field of a group fails to compile, and
+// so does calling build() before any field of an `exactly_one` group is set.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Login {
+    user: String,
+    #[builder(group = "auth", exactly_one)]
+    password: Option<String>,
+    #[builder(group = "auth")]
+    token: Option<String>,
+}
+
+fn main() {
+    let _ = Login::builder().user("ferris".to_owned()).build();
+
+    let _ = Login::builder()
+        .password("hunter2".to_owned())
+        .token("secret".to_owned());
+}
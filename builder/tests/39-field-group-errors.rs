@@ -0,0 +1,23 @@
+// Mistakes in groups are reported at compile time: a group without a rule,
+// rules that disagree, fields that could never be left unset, and
+// `required_if` naming a field that does not exist.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Connection {
+    #[builder(group = "auth")]
+    password: Option<String>,
+    #[builder(group = "proxy", exactly_one)]
+    proxy_host: Option<String>,
+    #[builder(group = "proxy", at_most_one)]
+    socks_host: Option<String>,
+    #[builder(group = "tls", exactly_one)]
+    cert: String,
+    #[builder(at_most_one)]
+    key: Option<String>,
+    #[builder(required_if = "proxy_hots")]
+    proxy_port: Option<u16>,
+}
+
+fn main() {}
@@ -0,0 +1,93 @@
+// Common checks can be declared on the fields with
+// #[builder(validate(...))]: `range` and `len` check the value or its length
+// against a range, `non_empty` rejects empty collections, and `with` calls a
+// function that returns `Result<(), E>` with a displayable error. `build()`
+// runs every check and reports all of the violations in one error.
+//
+// The validators run along with the other checks of build(): missing fields,
+// groups and `required_if`. When several of them fail, build() returns every
+// error in `Multiple`, in that order.
+
+use derive_builder::Builder;
+
+fn no_spaces(name: &String) -> Result<(), &'static str> {
+    if name.contains(' ') {
+        Err("must not contain spaces")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(validate(range = 1..=65535))]
+    port: u32,
+    #[builder(validate(len = 1..=16, with = "no_spaces"))]
+    name: String,
+    #[builder(each = "tag", validate(non_empty))]
+    tags: Vec<String>,
+    #[builder(validate(range = 1..=64))]
+    workers: Option<usize>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Listener {
+    host: String,
+    #[builder(validate(range = 1..=65535))]
+    port: u32,
+    #[builder(group = "tls", exactly_one)]
+    cert: Option<String>,
+    #[builder(group = "tls")]
+    insecure: Option<bool>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .port(8080)
+        .name("api".to_owned())
+        .tag("web".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, None);
+
+    let err = Server::builder()
+        .port(0)
+        .name("my api server is here".to_owned())
+        .workers(128)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ServerBuilderError::InvalidFields(vec![
+            "port must be in 1..=65535".to_owned(),
+            "name must have a length in 1..=16".to_owned(),
+            "name: must not contain spaces".to_owned(),
+            "tags must not be empty".to_owned(),
+            "workers must be in 1..=64".to_owned(),
+        ])
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid fields: port must be in 1..=65535; name must have a length in 1..=16; \
+         name: must not contain spaces; tags must not be empty; workers must be in 1..=64"
+    );
+
+    let err = Listener::builder().port(0).build().unwrap_err();
+    assert_eq!(
+        err,
+        ListenerBuilderError::Multiple(vec![
+            ListenerBuilderError::MissingFields(vec!["host".to_owned()]),
+            ListenerBuilderError::MissingGroup {
+                group: "tls",
+                fields: &["cert", "insecure"],
+            },
+            ListenerBuilderError::InvalidFields(vec!["port must be in 1..=65535".to_owned()]),
+        ])
+    );
+    assert_eq!(
+        err.to_string(),
+        "host is not set; one field of group tls must be set: cert, insecure; \
+         invalid fields: port must be in 1..=65535"
+    );
+}
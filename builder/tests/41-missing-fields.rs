@@ -0,0 +1,69 @@
+// build() reports every required field that is not set, not only the first
+// one, always in `MissingFields`, and `missing_fields()` lists them before
+// build() is called. The fields of a #[builder(sub_builder)] field are listed
+// by their path, such as `tls.cert`, which is why the names are `String`s
+// rather than `&'static str`s.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: String,
+    env: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Tls {
+    cert: String,
+    key: String,
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    port: u16,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert_eq!(
+        builder.missing_fields(),
+        vec!["executable", "args", "current_dir"]
+    );
+
+    builder.args(vec![]);
+    assert_eq!(builder.missing_fields(), vec!["executable", "current_dir"]);
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec![
+            "executable".to_owned(),
+            "current_dir".to_owned(),
+        ])
+    );
+    assert_eq!(err.to_string(), "missing fields: executable, current_dir");
+
+    builder.executable("cargo".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFields(vec!["current_dir".to_owned()]));
+    assert_eq!(err.to_string(), "current_dir is not set");
+
+    builder.current_dir("..".to_owned());
+    assert!(builder.missing_fields().is_empty());
+    assert!(builder.build().is_ok());
+
+    let mut builder = Server::builder();
+    assert_eq!(builder.missing_fields(), vec!["port", "tls.cert", "tls.key"]);
+    builder.port(443).tls(|b| b.cert("cert.pem".to_owned()));
+    assert_eq!(builder.missing_fields(), vec!["tls.key"]);
+
+    let err = Server::builder().port(443).build().unwrap_err();
+    assert_eq!(
+        err,
+        ServerBuilderError::MissingFields(vec!["tls.cert".to_owned(), "tls.key".to_owned()])
+    );
+    assert_eq!(err.to_string(), "missing fields: tls.cert, tls.key");
+}
@@ -0,0 +1,44 @@
+// Fields that are always required can be made #[builder(start)] to have them
+// passed to `builder()` as positional arguments, in the order of the fields.
+// They have no setters and can never be missing. This works the same for
+// typestate builders, which then only track the remaining required fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Command {
+    #[builder(start)]
+    executable: String,
+    #[builder(start, setter(into))]
+    kind: String,
+    args: Vec<String>,
+    #[builder(default)]
+    priority: u8,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Job {
+    #[builder(start)]
+    name: String,
+    retries: u32,
+}
+
+fn main() {
+    let mut builder = Command::builder("cargo".to_owned(), "build");
+    assert_eq!(builder.missing_fields(), vec!["args"]);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFields(vec!["args".to_owned()]));
+
+    let command = builder.args(vec!["--release".to_owned()]).build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.kind, "build");
+    assert_eq!(command.priority, 0);
+
+    let copy = command.to_builder().build().unwrap();
+    assert_eq!(copy, command);
+
+    let job = Job::builder("backup".to_owned()).retries(3).build().unwrap();
+    assert_eq!(job.name, "backup");
+    assert_eq!(job.retries, 3);
+}
@@ -0,0 +1,68 @@
+// Every derived builder implements the `Builder` trait of this crate, with
+// the built type as `Output` and the error of build() as `Error`, and every
+// struct with a builder implements `Buildable`, whose `builder()` creates it.
+// Generic code can then create and build values without naming the builder.
+//
+// The trait is implemented for what build() takes, which is `&mut` the
+// builder unless the builder is owned. Typestate builders implement it only
+// once every required field is set.
+
+use derive_builder::{Buildable, Builder};
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Point<T> {
+    x: T,
+    y: T,
+}
+
+#[derive(Builder, Debug, PartialEq, Default)]
+#[builder(typestate)]
+pub struct Limits {
+    #[builder(default)]
+    max: u32,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+}
+
+fn finish<B: Builder>(builder: B) -> Result<B::Output, B::Error> {
+    builder.build()
+}
+
+fn default_of<T>() -> T
+where
+    T: Buildable,
+    T::Builder: Builder<Output = T>,
+    <T::Builder as Builder>::Error: std::fmt::Debug,
+{
+    T::builder().build().unwrap()
+}
+
+fn main() {
+    let mut builder = <Command as Buildable>::builder();
+    builder.executable("cargo".to_owned());
+    let command = finish(&mut builder).unwrap();
+    assert_eq!(command.executable, "cargo");
+
+    let err: CommandBuilderError = finish(&mut Command::builder()).unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFields(vec!["executable".to_owned()]));
+
+    let point = finish(Point::builder().x(1).y(2)).unwrap();
+    assert_eq!(point, Point { x: 1, y: 2 });
+
+    assert_eq!(default_of::<Limits>(), Limits::default());
+
+    let job = finish(Job::builder().name("backup".to_owned())).unwrap();
+    assert_eq!(job.name, "backup");
+}
@@ -0,0 +1,12 @@
+// Unions have no way to tell which field was set, so they are rejected with an
+// error pointing at the `union` keyword.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
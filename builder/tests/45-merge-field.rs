@@ -0,0 +1,20 @@
+// Builders only have a `merge` method with #[builder(merge_fn)], so a field
+// named `merge` gets its setter like any other.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct GitConfig {
+    merge: String,
+    rebase: bool,
+}
+
+fn main() {
+    let config = GitConfig::builder()
+        .merge("ff-only".to_owned())
+        .rebase(false)
+        .build()
+        .unwrap();
+    assert_eq!(config.merge, "ff-only");
+    assert!(!config.rebase);
+}
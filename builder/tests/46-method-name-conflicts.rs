@@ -0,0 +1,51 @@
+// Setters cannot share their name with the builder's own methods: `build` or
+// the name given in build_fn(name = "..."), `missing_fields`, `merge` with
+// #[builder(merge_fn)], `fill_from_env` and `from_env` for fields read from the
+// environment, and `parse_args` with #[builder(cli)]. Nor can two setters
+// share a name. The error points at the field, which can be renamed with
+// #[builder(name = "...")].
+//
+// Without merge_fn there is no `merge` method, so #[builder(merge = "...")]
+// on a field has nothing to apply to either.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(merge_fn)]
+pub struct GitConfig {
+    merge: String,
+    #[builder(each = "missing_fields")]
+    paths: Vec<String>,
+    #[builder(name = "rebase")]
+    build: bool,
+}
+
+#[derive(Builder)]
+#[builder(cli, env_prefix = "APP_")]
+pub struct App {
+    parse_args: bool,
+    fill_from_env: bool,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    arg: String,
+}
+
+#[derive(Builder, Clone)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(sub_builder)]
+    tls: Tls,
+    tls_mut: bool,
+}
+
+#[derive(Builder)]
+pub struct Remote {
+    #[builder(each = "url", merge = "replace")]
+    urls: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+// The type parameters that a typestate builder tracks its fields and groups
+// with are named apart, so a required field may be named after a group with a
+// `_group` suffix.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Login {
+    auth_group: String,
+    #[builder(group = "auth", exactly_one)]
+    password: Option<String>,
+    #[builder(group = "auth")]
+    token: Option<String>,
+}
+
+fn main() {
+    let login = Login::builder()
+        .token("secret".to_owned())
+        .auth_group("admins".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(login.auth_group, "admins");
+    assert_eq!(login.token.as_deref(), Some("secret"));
+    assert_eq!(login.password, None);
+}
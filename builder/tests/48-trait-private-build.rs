@@ -0,0 +1,30 @@
+// The Builder trait would make `build` callable wherever the builder is, so
+// builders whose `build` is private or restricted do not implement it.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(build_fn(name = "build_raw", private))]
+    pub struct Port {
+        pub number: u16,
+    }
+
+    #[derive(Builder)]
+    #[builder(vis = "pub(crate)")]
+    pub struct Host {
+        pub name: String,
+    }
+}
+
+fn finish<B: derive_builder::Builder>(builder: B) -> Result<B::Output, B::Error> {
+    builder.build()
+}
+
+fn main() {
+    let mut port = config::Port::builder();
+    let _ = finish(&mut port);
+
+    let mut host = config::Host::builder();
+    let _ = finish(&mut host);
+}
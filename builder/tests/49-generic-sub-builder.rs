@@ -0,0 +1,43 @@
+// A `sub_builder` field can hold the builder of a generic type, as long as the
+// field's type does not depend on the generic parameters of the outer type.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Limit<T> {
+    max: T,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Label<'a> {
+    text: &'a str,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Pool<T> {
+    items: Vec<T>,
+    #[builder(sub_builder)]
+    limit: Limit<u32>,
+    #[builder(sub_builder)]
+    label: Label<'static>,
+}
+
+fn main() {
+    let pool = Pool::builder()
+        .items(vec!["a", "b"])
+        .limit(|b| b.max(8))
+        .label(|b| b.text("pool"))
+        .build()
+        .unwrap();
+    assert_eq!(pool.items, ["a", "b"]);
+    assert_eq!(pool.limit, Limit { max: 8 });
+    assert_eq!(pool.label.text, "pool");
+
+    let err = Pool::<&str>::builder()
+        .items(vec![])
+        .label(|b| b.text("pool"))
+        .build()
+        .unwrap_err();
+    assert_eq!(err, PoolBuilderError::MissingFields(vec!["limit.max".to_owned()]));
+    assert_eq!(err.to_string(), "limit.max is not set");
+}
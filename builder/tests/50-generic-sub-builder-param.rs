@@ -0,0 +1,25 @@
+// The error of a `sub_builder` field's builder is part of the outer builder's
+// error, which has no generic parameters. Fields whose type depends on those of
+// the outer type are rejected.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone)]
+pub struct Limit<T> {
+    max: T,
+}
+
+#[derive(Builder, Clone)]
+pub struct Label<'a> {
+    text: &'a str,
+}
+
+#[derive(Builder)]
+pub struct Pool<'a, T: Clone> {
+    #[builder(sub_builder)]
+    limit: Limit<T>,
+    #[builder(sub_builder)]
+    label: Label<'a>,
+}
+
+fn main() {}
@@ -10,4 +10,45 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-enum-variants.rs");
+    t.compile_fail("tests/11-enum-unit-variant.rs");
+    t.pass("tests/12-tuple-struct.rs");
+    t.pass("tests/13-generics.rs");
+    t.pass("tests/14-typestate.rs");
+    t.compile_fail("tests/15-typestate-misuse.rs");
+    t.pass("tests/16-default.rs");
+    t.compile_fail("tests/17-default-type-mismatch.rs");
+    t.pass("tests/18-error-type.rs");
+    t.pass("tests/19-no-std.rs");
+    t.pass("tests/20-builder-pattern.rs");
+    t.pass("tests/21-each-collections.rs");
+    t.pass("tests/22-setter-options.rs");
+    t.pass("tests/23-build-fn.rs");
+    t.pass("tests/24-visibility.rs");
+    t.compile_fail("tests/25-private-storage.rs");
+    t.pass("tests/26-builder-attrs.rs");
+    t.pass("tests/27-setter-skip.rs");
+    t.compile_fail("tests/28-skip-without-default.rs");
+    t.compile_fail("tests/29-attribute-errors.rs");
+    t.pass("tests/30-try-setter.rs");
+    t.pass("tests/31-to-builder.rs");
+    t.pass("tests/32-merge.rs");
+    t.compile_fail("tests/33-merge-non-collection.rs");
+    t.pass("tests/34-env.rs");
+    t.pass("tests/35-cli.rs");
+    t.pass("tests/36-sub-builder.rs");
+    t.pass("tests/37-field-groups.rs");
+    t.compile_fail("tests/38-typestate-groups.rs");
+    t.compile_fail("tests/39-field-group-errors.rs");
+    t.pass("tests/40-validators.rs");
+    t.pass("tests/41-missing-fields.rs");
+    t.pass("tests/42-start-fields.rs");
+    t.pass("tests/43-traits.rs");
+    t.compile_fail("tests/44-union.rs");
+    t.pass("tests/45-merge-field.rs");
+    t.compile_fail("tests/46-method-name-conflicts.rs");
+    t.pass("tests/47-typestate-group-names.rs");
+    t.compile_fail("tests/48-trait-private-build.rs");
+    t.pass("tests/49-generic-sub-builder.rs");
+    t.compile_fail("tests/50-generic-sub-builder-param.rs");
 }